@@ -1,13 +1,66 @@
 pub const PRECISION_U128: u128 = 1000000000; // 10^9;
 pub const LP_MINT_DECIMALS: u8 = 6;
+pub const LP_MINT_SYMBOL: &str = "SLP";
+pub const LP_MINT_NAME_MINT_CHARS: usize = 4;
+pub const LP_MINT_NAME_SYMBOL_CHARS: usize = 10;
+pub const MAX_LP_MINT_URI_LEN: usize = 200;
 
 pub const MAXIMUM_FEE: u64 = 1000000000; // 50% with PRECISION_U64
 
 pub const CUSTOMED_FEE_BOUND: u64 = 50000000;
 
+pub const PAUSE_SWAP: u8 = 1 << 0;
+pub const PAUSE_DEPOSIT: u8 = 1 << 1;
+pub const PAUSE_WITHDRAW: u8 = 1 << 2;
+pub const PAUSE_FEE_CLAIM: u8 = 1 << 3;
+pub const PAUSE_ALL: u8 = PAUSE_SWAP | PAUSE_DEPOSIT | PAUSE_WITHDRAW | PAUSE_FEE_CLAIM;
+
+pub const ROLE_FEE_MANAGER: u8 = 1 << 0;
+pub const ROLE_PAUSER: u8 = 1 << 1;
+pub const ROLE_FEE_COLLECTOR: u8 = 1 << 2;
+pub const ROLE_REFERRAL_MANAGER: u8 = 1 << 3;
+pub const ROLE_POOL_MANAGER: u8 = 1 << 4;
+pub const ROLE_ALL: u8 =
+    ROLE_FEE_MANAGER | ROLE_PAUSER | ROLE_FEE_COLLECTOR | ROLE_REFERRAL_MANAGER | ROLE_POOL_MANAGER;
+pub const MAX_ROLE_GRANTS: usize = 8;
+
+pub const RISK_FREEZE_AUTHORITY: u8 = 1 << 0;
+pub const RISK_MINT_AUTHORITY: u8 = 1 << 1;
+pub const RISK_DANGEROUS_EXTENSIONS: u8 = 1 << 2;
+pub const RISK_ALL: u8 = RISK_FREEZE_AUTHORITY | RISK_MINT_AUTHORITY | RISK_DANGEROUS_EXTENSIONS;
+
+pub const MIN_TIMELOCK_DELAY: i64 = 60; // 1 minute
+pub const MAX_TIMELOCK_DELAY: i64 = 2592000; // 30 days
+
+pub const PROGRAM_VERSION: u16 = 1;
+pub const POOL_VERSION: u8 = 2;
+pub const PLATFORM_CONFIG_VERSION: u8 = 1;
+pub const REFERRER_VERSION: u8 = 1;
+// Baseline configs are not PDAs, so they stay out of the index space of the seeded configs
+pub const LEGACY_PLATFORM_CONFIG_INDEX: u16 = u16::MAX;
+
+pub const MAX_POOLS_PER_INDEX_PAGE: usize = 256;
+
+// Swaps of pools in different shards do not contend for the same stats account
+pub const SWAP_COUNTER_SHARDS: u64 = 16;
+
+// The direct referrer and its uplines
+pub const MAX_REFERRAL_TIERS: usize = 3;
+pub const MIN_REFERRAL_CODE_LEN: usize = 3;
+pub const MAX_REFERRAL_CODE_LEN: usize = 16;
+pub const MAX_REFERRER_COOLDOWN: i64 = 2592000; // 30 days
+pub const MAX_REFERRAL_STATS_MINTS: usize = 64;
+
+pub const CURVE_CONSTANT_PRODUCT: u8 = 1 << 0;
+pub const SUPPORTED_CURVES: u8 = CURVE_CONSTANT_PRODUCT;
+
 pub const PUBKEY_SIZE: usize = 32;
 pub const U64_SIZE: usize = 8;
 pub const I64_SIZE: usize = 8;
+pub const U32_SIZE: usize = 4;
+pub const U16_SIZE: usize = 2;
 pub const U8_SIZE: usize = 1;
+pub const BOOL_SIZE: usize = 1;
 pub const VECTOR_SIZE: usize = 4;
+pub const RESERVED_SIZE: usize = 64;
 pub const ACCOUNT_DISCRIMINATOR: usize = 8;
@@ -20,4 +20,44 @@ pub enum ErrorCode {
     InvalidPlatformConfig,
     #[msg("Invalid referer")]
     InvalidReferer,
+    #[msg("Invalid fee receiver")]
+    InvalidFeeReceiver,
+    #[msg("Missing fee account")]
+    MissingFeeAccount,
+    #[msg("Operation paused")]
+    OperationPaused,
+    #[msg("Platform emergency paused")]
+    EmergencyPaused,
+    #[msg("Too many role grants")]
+    TooManyRoleGrants,
+    #[msg("Timelock not elapsed")]
+    TimelockNotElapsed,
+    #[msg("Token not allowed")]
+    TokenNotAllowed,
+    #[msg("Unsafe mint")]
+    UnsafeMint,
+    #[msg("Unsorted mints")]
+    UnsortedMints,
+    #[msg("Pool index page full")]
+    PoolIndexFull,
+    #[msg("Account already migrated")]
+    AlreadyMigrated,
+    #[msg("Unsupported account layout")]
+    UnsupportedLayout,
+    #[msg("Missing referral account")]
+    MissingReferralAccount,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Referral cycle")]
+    ReferralCycle,
+    #[msg("Invalid referral code")]
+    InvalidReferralCode,
+    #[msg("Referrer cooldown not elapsed")]
+    ReferrerCooldown,
+    #[msg("Invalid pool index page")]
+    InvalidIndexPage,
+    #[msg("Pool missing from the index page")]
+    PoolNotIndexed,
+    #[msg("Pending change still open")]
+    PendingChangeOpen,
 }
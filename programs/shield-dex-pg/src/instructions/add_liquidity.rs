@@ -1,6 +1,13 @@
-use crate::{errors::ErrorCode, schema::pool::Pool};
+use crate::{
+    constants::PAUSE_DEPOSIT,
+    errors::ErrorCode,
+    schema::{
+        platform_config::PlatformConfig,
+        pool::{Pool, PoolState},
+    },
+};
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token, token};
+use anchor_spl::{associated_token, token_interface};
 
 #[event]
 pub struct AddLiquidityEvent {
@@ -9,8 +16,14 @@ pub struct AddLiquidityEvent {
     pub a: u64,
     pub b: u64,
     pub lp: u64,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub lp_supply: u64,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct AddLiquidity<'info> {
     /// Authority
@@ -22,50 +35,65 @@ pub struct AddLiquidity<'info> {
     has_one = mint_b @ ErrorCode::UnmatchPool,
     has_one = lp_mint @ ErrorCode::UnmatchPool
   )]
-    pub pool: Account<'info, Pool>,
+    pub pool: AccountLoader<'info, Pool>,
+    /// Which config the pool belongs to.
+    #[account(address = pool.load()?.tax @ ErrorCode::InvalidPlatformConfig)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
     /// Mint A
-    pub mint_a: Box<Account<'info, token::Mint>>,
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
     mut,
     associated_token::mint = mint_a,
-    associated_token::authority = authority
+    associated_token::authority = authority,
+    associated_token::token_program = token_program_a
   )]
-    pub src_a: Box<Account<'info, token::TokenAccount>>,
+    pub src_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
     mut,
     associated_token::mint = mint_a,
-    associated_token::authority = escrow
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program_a
   )]
-    pub treasury_a: Box<Account<'info, token::TokenAccount>>,
+    pub treasury_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     /// Mint B
-    pub mint_b: Box<Account<'info, token::Mint>>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
     mut,
     associated_token::mint = mint_b,
-    associated_token::authority = authority
+    associated_token::authority = authority,
+    associated_token::token_program = token_program_b
   )]
-    pub src_b: Box<Account<'info, token::TokenAccount>>,
+    pub src_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
     mut,
     associated_token::mint = mint_b,
-    associated_token::authority = escrow
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program_b
   )]
-    pub treasury_b: Box<Account<'info, token::TokenAccount>>,
+    pub treasury_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     // LP Mint
-    #[account(mut)]
-    pub lp_mint: Box<Account<'info, token::Mint>>,
+    #[account(mut, mint::token_program = lp_token_program)]
+    pub lp_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
     init_if_needed,
     payer = authority,
     associated_token::mint = lp_mint,
-    associated_token::authority = authority
+    associated_token::authority = authority,
+    associated_token::token_program = lp_token_program
   )]
-    pub dst_lp: Box<Account<'info, token::TokenAccount>>,
+    pub dst_lp: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     /// CHECK: The pool escrow
     #[account(seeds = ["escrow".as_bytes(), &pool.key().to_bytes()], bump)]
     pub escrow: AccountInfo<'info>,
     /// System programs
-    pub token_program: Program<'info, token::Token>,
+    /// Token program of mint A, SPL Token or Token-2022
+    pub token_program_a: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of mint B, SPL Token or Token-2022
+    pub token_program_b: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of the LP mint, Token-2022 for pools created with metadata
+    pub lp_token_program: Interface<'info, token_interface::TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -73,52 +101,62 @@ pub struct AddLiquidity<'info> {
 
 impl AddLiquidity<'_> {
     pub fn invoke(ctx: Context<AddLiquidity>, a: u64, b: u64) -> Result<()> {
-        let pool = &ctx.accounts.pool;
+        let pool = ctx.accounts.pool.load()?;
         let seeds: &[&[&[u8]]] = &[&[
             "escrow".as_ref(),
-            &pool.key().to_bytes(),
+            &ctx.accounts.pool.key().to_bytes(),
             &[ctx.bumps.escrow],
         ]];
-        msg!(
-            "referrer: system_program: {}",
-            ctx.accounts.system_program.key()
-        );
 
-        if a <= 0 || b <= 0 {
+        if ctx.accounts.platform_config.emergency_paused {
+            return err!(ErrorCode::EmergencyPaused);
+        }
+        // Canceled pools are withdraw-only
+        if pool.state() == PoolState::Canceled {
+            return err!(ErrorCode::InvalidState);
+        }
+        if pool.is_paused(PAUSE_DEPOSIT) {
+            return err!(ErrorCode::OperationPaused);
+        }
+        if a == 0 || b == 0 {
             return err!(ErrorCode::InvalidParams);
         }
 
         // Deposit token A
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.token_program_a.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.src_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
                     to: ctx.accounts.treasury_a.to_account_info(),
                     authority: ctx.accounts.authority.to_account_info(),
                 },
             ),
             a,
+            ctx.accounts.mint_a.decimals,
         )?;
         // Deposit token B
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.token_program_b.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.src_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
                     to: ctx.accounts.treasury_b.to_account_info(),
                     authority: ctx.accounts.authority.to_account_info(),
                 },
             ),
             b,
+            ctx.accounts.mint_b.decimals,
         )?;
 
         // Mint LP tokens
         let lp = Pool::calc_liquidity(a, b).ok_or(ErrorCode::Overflow)?;
-        token::mint_to(
+        token_interface::mint_to(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::MintTo {
+                ctx.accounts.lp_token_program.to_account_info(),
+                token_interface::MintTo {
                     mint: ctx.accounts.lp_mint.to_account_info(),
                     to: ctx.accounts.dst_lp.to_account_info(),
                     authority: ctx.accounts.escrow.to_account_info(),
@@ -128,12 +166,39 @@ impl AddLiquidity<'_> {
             lp,
         )?;
 
-        emit!(AddLiquidityEvent {
+        // Reserves and supply after the deposit, the accounts are not reloaded
+        let (reserve_a, reserve_b) = pool
+            .calc_reserves(
+                ctx.accounts
+                    .treasury_a
+                    .amount
+                    .checked_add(a)
+                    .ok_or(ErrorCode::Overflow)?,
+                ctx.accounts
+                    .treasury_b
+                    .amount
+                    .checked_add(b)
+                    .ok_or(ErrorCode::Overflow)?,
+            )
+            .ok_or(ErrorCode::Overflow)?;
+        let clock = Clock::get()?;
+
+        emit_cpi!(AddLiquidityEvent {
             authority: ctx.accounts.authority.key(),
-            pool: pool.key(),
+            pool: ctx.accounts.pool.key(),
             a,
             b,
-            lp
+            lp,
+            reserve_a,
+            reserve_b,
+            lp_supply: ctx
+                .accounts
+                .lp_mint
+                .supply
+                .checked_add(lp)
+                .ok_or(ErrorCode::Overflow)?,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
         });
 
         Ok(())
@@ -1,21 +1,48 @@
-use crate::{errors::ErrorCode, schema::platform_config::PlatformConfig};
+use crate::{
+    constants::{
+        LEGACY_PLATFORM_CONFIG_INDEX, MAX_TIMELOCK_DELAY, MIN_TIMELOCK_DELAY,
+        PLATFORM_CONFIG_VERSION,
+    },
+    errors::ErrorCode,
+    schema::{
+        platform_config::{FeeTier, PlatformConfig},
+        program_state::ProgramState,
+    },
+};
 use anchor_lang::prelude::*;
 
+#[event]
+pub struct CreatePlatformConfigEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub index: u16,
+    pub fee_tier: FeeTier,
+    pub fee_receiver: Pubkey,
+    pub timelock_delay: i64,
+    pub created_at: i64,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct CreatePlatformConfig<'info> {
     /// Address to be set as protocol owner.
     #[account(
         mut,
-        address = crate::admin::id() @ ErrorCode::Unauthorized
+        address = program_state.admin @ ErrorCode::Unauthorized
     )]
     pub owner: Signer<'info>,
 
+    /// Program state counting the configs, used to derive the index of the next config.
+    #[account(mut, seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
     /// Initialize config state account to store protocol owner address and fee rates.
     #[account(
         init,
         payer = owner,
         space = PlatformConfig::LEN,
-        
+        seeds = ["platform_config".as_bytes(), &program_state.total_configs.to_le_bytes()],
+        bump
     )]
     pub platform_config: Account<'info, PlatformConfig>,
 
@@ -23,12 +50,53 @@ pub struct CreatePlatformConfig<'info> {
 }
 
 impl CreatePlatformConfig<'_> {
-    pub fn invoke(ctx: Context<CreatePlatformConfig>, tax: u64) -> Result<()> {
+    pub fn invoke(
+        ctx: Context<CreatePlatformConfig>,
+        fee_tier: FeeTier,
+        fee_receiver: Pubkey,
+        timelock_delay: i64,
+    ) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
         let platform_config = &mut ctx.accounts.platform_config;
-        platform_config.tax = tax;
+
+        if !fee_tier.is_valid() {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if fee_receiver == Pubkey::default() {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if !(MIN_TIMELOCK_DELAY..=MAX_TIMELOCK_DELAY).contains(&timelock_delay) {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if program_state.total_configs == LEGACY_PLATFORM_CONFIG_INDEX {
+            return err!(ErrorCode::Overflow);
+        }
+
+        platform_config.index = program_state.total_configs;
+        platform_config.bump = ctx.bumps.platform_config;
+        platform_config.version = PLATFORM_CONFIG_VERSION;
+        platform_config.set_fee_tier(&fee_tier);
+        platform_config.fee_receiver = fee_receiver;
+        platform_config.timelock_delay = timelock_delay;
         platform_config.created_at = Clock::get()?.unix_timestamp;
         platform_config.updated_at = Clock::get()?.unix_timestamp;
 
+        program_state.total_configs = program_state
+            .total_configs
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        program_state.updated_at = platform_config.updated_at;
+
+        emit_cpi!(CreatePlatformConfigEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            index: platform_config.index,
+            fee_tier,
+            fee_receiver,
+            timelock_delay,
+            created_at: platform_config.created_at
+        });
+
         Ok(())
     }
 }
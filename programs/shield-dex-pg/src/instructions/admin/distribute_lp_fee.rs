@@ -1,58 +1,99 @@
+use crate::constants::{PAUSE_FEE_CLAIM, ROLE_FEE_COLLECTOR};
 use crate::errors::ErrorCode;
+use crate::schema::access_control::AccessControl;
 use crate::schema::platform_config::PlatformConfig;
 use crate::schema::pool::Pool;
+use crate::schema::program_state::ProgramState;
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token;
-use anchor_spl::token;
-use anchor_spl::token::Token;
+use anchor_spl::token_interface;
 
+#[event]
+pub struct DistributeLpFeeEvent {
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub recipient_a: Pubkey,
+    pub recipient_b: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub lp_fees_mint_a: u64,
+    pub lp_fees_mint_b: u64,
+    pub distributed_at: i64,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct DistributeLpFee<'info> {
-    /// Only admin or owner can collect fee now
-    #[account(constraint = (owner.key() == pool.authority || owner.key() == crate::admin::id()) @ ErrorCode::Unauthorized)]
+    /// Only admin, owner or fee collector can collect fee now
+    #[account(
+      constraint = (owner.key() == program_state.admin
+        || pool.load()?.is_granted(access_control.as_deref(), owner.key(), ROLE_FEE_COLLECTOR)) @ ErrorCode::Unauthorized
+    )]
     pub owner: Signer<'info>,
 
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
     /// CHECK: The pool escrow
     #[account(seeds = ["escrow".as_bytes(), &pool.key().to_bytes()], bump)]
     pub escrow: AccountInfo<'info>,
 
     /// Pool state stores accumulated protocol fee amount
-    #[account(mut)]
-    pub pool: Account<'info, Pool>,
+    #[account(
+      mut,
+      has_one = mint_a @ ErrorCode::UnmatchPool,
+      has_one = mint_b @ ErrorCode::UnmatchPool
+    )]
+    pub pool: AccountLoader<'info, Pool>,
+
+    /// Access control
+    #[account(
+      has_one = pool @ ErrorCode::UnmatchPool,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Option<Account<'info, AccessControl>>,
 
     /// Platform config account stores owner
-    #[account(address = pool.tax)]
+    #[account(address = pool.load()?.tax)]
     pub platform_config: Account<'info, PlatformConfig>,
 
+    /// Mint A
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, token_interface::Mint>>,
     /// Mint B
-    pub mint_a: Box<Account<'info, token::Mint>>,
-    /// Mint B
-    pub mint_b: Box<Account<'info, token::Mint>>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, token_interface::Mint>>,
 
     /// The address that holds pool tokens for token_a
     #[account(
         mut,
         associated_token::mint = mint_a,
-        associated_token::authority = escrow
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program_a
       )]
-    pub treasury_a: Box<Account<'info, token::TokenAccount>>,
+    pub treasury_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     /// The address that holds pool tokens for token_b
     #[account(
         mut,
         associated_token::mint = mint_b,
-        associated_token::authority = escrow
+        associated_token::authority = escrow,
+        associated_token::token_program = token_program_b
       )]
-    pub treasury_b: Box<Account<'info, token::TokenAccount>>,
+    pub treasury_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     /// The address that receives the collected token_0 protocol fees
     #[account(mut)]
-    pub recipient_token_a_account: Box<Account<'info, token::TokenAccount>>,
+    pub recipient_token_a_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
 
     /// The address that receives the collected token_1 protocol fees
     #[account(mut)]
-    pub recipient_token_b_account: Box<Account<'info, token::TokenAccount>>,
+    pub recipient_token_b_account: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
 
-    /// The SPL program to perform token transfers
-    pub token_program: Program<'info, Token>,
+    /// Token program of mint A, SPL Token or Token-2022
+    pub token_program_a: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of mint B, SPL Token or Token-2022
+    pub token_program_b: Interface<'info, token_interface::TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
 }
 
@@ -62,14 +103,15 @@ impl DistributeLpFee<'_> {
         amount_a_requested: u64,
         amount_b_requested: u64,
     ) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
+        let pool_key = ctx.accounts.pool.key();
+        let mut pool = ctx.accounts.pool.load_mut()?;
         let amount_a: u64;
         let amount_b: u64;
-        let auth_bump: &[&[&[u8]]] = &[&[
-            "escrow".as_ref(),
-            &pool.key().to_bytes(),
-            &[ctx.bumps.escrow],
-        ]];
+        let auth_bump: &[&[&[u8]]] =
+            &[&["escrow".as_ref(), &pool_key.to_bytes(), &[ctx.bumps.escrow]]];
+        if pool.is_paused(PAUSE_FEE_CLAIM) {
+            return err!(ErrorCode::OperationPaused);
+        }
         {
             amount_a = amount_a_requested.min(pool.lp_fees_mint_a);
             amount_b = amount_b_requested.min(pool.lp_fees_mint_b);
@@ -78,32 +120,48 @@ impl DistributeLpFee<'_> {
             pool.lp_fees_mint_b = pool.lp_fees_mint_b.checked_sub(amount_b).unwrap();
         }
 
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.token_program_a.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.treasury_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
                     to: ctx.accounts.recipient_token_a_account.to_account_info(),
                     authority: ctx.accounts.escrow.to_account_info(),
                 },
                 auth_bump,
             ),
             amount_a,
+            ctx.accounts.mint_a.decimals,
         )?;
 
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.token_program_b.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.treasury_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
                     to: ctx.accounts.recipient_token_b_account.to_account_info(),
                     authority: ctx.accounts.escrow.to_account_info(),
                 },
                 auth_bump,
             ),
             amount_b,
+            ctx.accounts.mint_b.decimals,
         )?;
 
+        emit_cpi!(DistributeLpFeeEvent {
+            authority: ctx.accounts.owner.key(),
+            pool: pool_key,
+            recipient_a: ctx.accounts.recipient_token_a_account.key(),
+            recipient_b: ctx.accounts.recipient_token_b_account.key(),
+            amount_a,
+            amount_b,
+            lp_fees_mint_a: pool.lp_fees_mint_a,
+            lp_fees_mint_b: pool.lp_fees_mint_b,
+            distributed_at: Clock::get()?.unix_timestamp
+        });
+
         Ok(())
     }
 }
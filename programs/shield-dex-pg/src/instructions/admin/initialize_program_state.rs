@@ -0,0 +1,54 @@
+use crate::{constants::PROGRAM_VERSION, errors::ErrorCode, schema::program_state::ProgramState};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct InitializeProgramStateEvent {
+    pub admin: Pubkey,
+    pub program_state: Pubkey,
+    pub version: u16,
+    pub created_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeProgramState<'info> {
+    /// Only admin can set up the program
+    #[account(
+        mut,
+        address = crate::admin::id() @ ErrorCode::Unauthorized
+    )]
+    pub owner: Signer<'info>,
+
+    /// Program state, created once
+    #[account(
+        init,
+        payer = owner,
+        space = ProgramState::LEN,
+        seeds = ["program_state".as_bytes()],
+        bump
+    )]
+    pub program_state: Account<'info, ProgramState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl InitializeProgramState<'_> {
+    pub fn invoke(ctx: Context<InitializeProgramState>) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+
+        program_state.bump = ctx.bumps.program_state;
+        program_state.admin = ctx.accounts.owner.key();
+        program_state.version = PROGRAM_VERSION;
+        program_state.created_at = Clock::get()?.unix_timestamp;
+        program_state.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(InitializeProgramStateEvent {
+            admin: program_state.admin,
+            program_state: program_state.key(),
+            version: program_state.version,
+            created_at: program_state.created_at
+        });
+
+        Ok(())
+    }
+}
@@ -1,3 +1,15 @@
 pub mod create_platform_config;
 pub mod distribute_lp_fee;
+pub mod initialize_program_state;
+pub mod set_emergency_pause;
+pub mod set_token_status;
+pub mod update_admin;
+pub mod update_create_pool_fee;
+pub mod update_fee_receiver;
+pub mod update_guardian;
+pub mod update_mint_policy;
 pub mod update_platform_config;
+pub mod update_referral_tiers;
+pub mod update_referrer_cooldown;
+pub mod update_timelock_delay;
+pub mod update_token_policy;
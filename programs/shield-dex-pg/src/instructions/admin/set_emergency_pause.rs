@@ -0,0 +1,54 @@
+use crate::errors::ErrorCode;
+use crate::schema::platform_config::PlatformConfig;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct SetEmergencyPauseEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub emergency_paused: bool,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(emergency_paused: bool)]
+pub struct SetEmergencyPause<'info> {
+    /// Admin or guardian can pull the kill switch, only admin can release it
+    #[account(
+      constraint = (owner.key() == program_state.admin
+        || (emergency_paused && owner.key() == platform_config.guardian)) @ ErrorCode::Unauthorized
+    )]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the kill switch
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl SetEmergencyPause<'_> {
+    pub fn invoke(ctx: Context<SetEmergencyPause>, emergency_paused: bool) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        if platform_config.emergency_paused == emergency_paused {
+            return err!(ErrorCode::InvalidState);
+        }
+
+        platform_config.emergency_paused = emergency_paused;
+        platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(SetEmergencyPauseEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            emergency_paused,
+            updated_at: platform_config.updated_at
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,66 @@
+use crate::errors::ErrorCode;
+use crate::schema::program_state::ProgramState;
+use crate::schema::token_registry::{TokenRegistry, TokenStatus};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface;
+
+#[event]
+pub struct SetTokenStatusEvent {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub old_status: TokenStatus,
+    pub status: TokenStatus,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetTokenStatus<'info> {
+    /// Only admin can manage the token registry
+    #[account(mut, address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Mint to list
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+
+    /// Token registry
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = TokenRegistry::LEN,
+        seeds = ["token_registry".as_bytes(), &mint.key().to_bytes()],
+        bump
+    )]
+    pub token_registry: Account<'info, TokenRegistry>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl SetTokenStatus<'_> {
+    pub fn invoke(ctx: Context<SetTokenStatus>, status: TokenStatus) -> Result<()> {
+        let token_registry = &mut ctx.accounts.token_registry;
+        // A registry created here reads as unlisted
+        let old_status = token_registry.status;
+
+        if token_registry.mint == Pubkey::default() {
+            token_registry.mint = ctx.accounts.mint.key();
+            token_registry.created_at = Clock::get()?.unix_timestamp;
+        }
+        token_registry.status = status;
+        token_registry.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(SetTokenStatusEvent {
+            authority: ctx.accounts.owner.key(),
+            mint: token_registry.mint,
+            old_status,
+            status,
+            updated_at: token_registry.updated_at
+        });
+
+        Ok(())
+    }
+}
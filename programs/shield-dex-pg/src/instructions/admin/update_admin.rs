@@ -0,0 +1,49 @@
+use crate::errors::ErrorCode;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateAdminEvent {
+    pub authority: Pubkey,
+    pub program_state: Pubkey,
+    pub old_admin: Pubkey,
+    pub admin: Pubkey,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateAdmin<'info> {
+    /// Only admin can hand over the program
+    #[account(address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(mut, seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+}
+
+impl UpdateAdmin<'_> {
+    pub fn invoke(ctx: Context<UpdateAdmin>, admin: Pubkey) -> Result<()> {
+        let program_state = &mut ctx.accounts.program_state;
+
+        if admin == Pubkey::default() {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        let old_admin = program_state.admin;
+
+        program_state.admin = admin;
+        program_state.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdateAdminEvent {
+            authority: ctx.accounts.owner.key(),
+            program_state: program_state.key(),
+            old_admin,
+            admin,
+            updated_at: program_state.updated_at
+        });
+
+        Ok(())
+    }
+}
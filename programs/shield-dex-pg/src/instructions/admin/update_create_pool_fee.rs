@@ -0,0 +1,74 @@
+use crate::errors::ErrorCode;
+use crate::schema::platform_config::PlatformConfig;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateCreatePoolFeeEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_create_pool_fee: u64,
+    pub create_pool_fee: u64,
+    pub old_custom_fee_surcharge: u64,
+    pub custom_fee_surcharge: u64,
+    pub old_create_pool_fee_mint: Pubkey,
+    pub create_pool_fee_mint: Pubkey,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateCreatePoolFee<'info> {
+    /// Only admin can change the pool creation pricing
+    #[account(address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the pool creation pricing
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl UpdateCreatePoolFee<'_> {
+    ///
+    /// Set `create_pool_fee_mint` to the default pubkey to charge the fee in SOL
+    ///
+    pub fn invoke(
+        ctx: Context<UpdateCreatePoolFee>,
+        create_pool_fee: u64,
+        custom_fee_surcharge: u64,
+        create_pool_fee_mint: Pubkey,
+    ) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        if create_pool_fee.checked_add(custom_fee_surcharge).is_none() {
+            return err!(ErrorCode::Overflow);
+        }
+
+        let old_create_pool_fee = platform_config.create_pool_fee;
+        let old_custom_fee_surcharge = platform_config.custom_fee_surcharge;
+        let old_create_pool_fee_mint = platform_config.create_pool_fee_mint;
+
+        platform_config.create_pool_fee = create_pool_fee;
+        platform_config.custom_fee_surcharge = custom_fee_surcharge;
+        platform_config.create_pool_fee_mint = create_pool_fee_mint;
+        platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdateCreatePoolFeeEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            old_create_pool_fee,
+            create_pool_fee,
+            old_custom_fee_surcharge,
+            custom_fee_surcharge,
+            old_create_pool_fee_mint,
+            create_pool_fee_mint,
+            updated_at: platform_config.updated_at
+        });
+
+        Ok(())
+    }
+}
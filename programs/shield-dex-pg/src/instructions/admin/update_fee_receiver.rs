@@ -0,0 +1,54 @@
+use crate::errors::ErrorCode;
+use crate::schema::platform_config::PlatformConfig;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateFeeReceiverEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_fee_receiver: Pubkey,
+    pub fee_receiver: Pubkey,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateFeeReceiver<'info> {
+    /// Only admin can change the fee receiver
+    #[account(address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the fee receiver
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl UpdateFeeReceiver<'_> {
+    pub fn invoke(ctx: Context<UpdateFeeReceiver>, fee_receiver: Pubkey) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        if fee_receiver == Pubkey::default() {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        let old_fee_receiver = platform_config.fee_receiver;
+
+        platform_config.fee_receiver = fee_receiver;
+        platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdateFeeReceiverEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            old_fee_receiver,
+            fee_receiver,
+            updated_at: platform_config.updated_at
+        });
+
+        Ok(())
+    }
+}
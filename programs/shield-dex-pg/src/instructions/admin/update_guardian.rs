@@ -0,0 +1,53 @@
+use crate::errors::ErrorCode;
+use crate::schema::platform_config::PlatformConfig;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateGuardianEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_guardian: Pubkey,
+    pub guardian: Pubkey,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateGuardian<'info> {
+    /// Only admin can designate the guardian
+    #[account(address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the guardian
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl UpdateGuardian<'_> {
+    ///
+    /// Set the guardian to the default pubkey to revoke it
+    ///
+    pub fn invoke(ctx: Context<UpdateGuardian>, guardian: Pubkey) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        let old_guardian = platform_config.guardian;
+
+        platform_config.guardian = guardian;
+        platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdateGuardianEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            old_guardian,
+            guardian,
+            updated_at: platform_config.updated_at
+        });
+
+        Ok(())
+    }
+}
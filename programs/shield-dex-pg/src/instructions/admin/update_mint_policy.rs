@@ -0,0 +1,59 @@
+use crate::constants::RISK_ALL;
+use crate::errors::ErrorCode;
+use crate::schema::platform_config::PlatformConfig;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateMintPolicyEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_rejected_mint_risks: u8,
+    pub rejected_mint_risks: u8,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateMintPolicy<'info> {
+    /// Only admin can change the mint safety policy
+    #[account(address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the mint safety policy
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl UpdateMintPolicy<'_> {
+    ///
+    /// Mints with any of the rejected risks cannot be pooled,
+    /// the other risks are only flagged on the pool
+    ///
+    pub fn invoke(ctx: Context<UpdateMintPolicy>, rejected_mint_risks: u8) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        if rejected_mint_risks & !RISK_ALL != 0 {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        let old_rejected_mint_risks = platform_config.rejected_mint_risks;
+
+        platform_config.rejected_mint_risks = rejected_mint_risks;
+        platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdateMintPolicyEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            old_rejected_mint_risks,
+            rejected_mint_risks,
+            updated_at: platform_config.updated_at
+        });
+
+        Ok(())
+    }
+}
@@ -1,25 +1,76 @@
 use crate::errors::ErrorCode;
-use crate::schema::platform_config::PlatformConfig;
+use crate::schema::{
+    pending_change::{ParamChange, PendingChange},
+    platform_config::{FeeTier, PlatformConfig},
+    program_state::ProgramState,
+};
 use anchor_lang::prelude::*;
 
+#[event]
+pub struct UpdatePlatformConfigEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_fee_tier: FeeTier,
+    pub fee_tier: FeeTier,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdatePlatformConfig<'info> {
     /// Address to be set as protocol owner.
     #[account(
         mut,
-        address = crate::admin::id() @ ErrorCode::Unauthorized
+        address = program_state.admin @ ErrorCode::Unauthorized
     )]
     pub owner: Signer<'info>,
 
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
     /// Initialize config state account to store protocol owner address and fee rates.
-    #[account(mut)]
     pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Pending change
+    #[account(
+        init,
+        payer = owner,
+        space = PendingChange::LEN,
+        seeds = [
+            "pending_change".as_bytes(),
+            &platform_config.key().to_bytes(),
+            &[ParamChange::FEE_TIER]
+        ],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    pub system_program: Program<'info, System>,
 }
 
 impl UpdatePlatformConfig<'_> {
-    pub fn invoke(ctx: Context<UpdatePlatformConfig>, tax: u64) -> Result<()> {
-        let platform_config = &mut ctx.accounts.platform_config;
-        platform_config.tax = tax;
+    ///
+    /// Queue the fee tier change, executable by anyone after `eta`
+    ///
+    pub fn invoke(ctx: Context<UpdatePlatformConfig>, fee_tier: FeeTier, eta: i64) -> Result<()> {
+        let platform_config = &ctx.accounts.platform_config;
+
+        if !fee_tier.is_valid() {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if !platform_config.is_valid_eta(eta, Clock::get()?.unix_timestamp) {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        let event = PendingChange::propose(
+            &mut ctx.accounts.pending_change,
+            platform_config.key(),
+            ctx.accounts.owner.key(),
+            ParamChange::FeeTier(fee_tier),
+            eta,
+        )?;
+        emit_cpi!(event);
 
         Ok(())
     }
@@ -0,0 +1,61 @@
+use crate::constants::MAX_REFERRAL_TIERS;
+use crate::errors::ErrorCode;
+use crate::schema::platform_config::PlatformConfig;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateReferralTiersEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_referral_tier_shares: [u64; MAX_REFERRAL_TIERS - 1],
+    pub referral_tier_shares: [u64; MAX_REFERRAL_TIERS - 1],
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateReferralTiers<'info> {
+    /// Only admin can change the referral tiers
+    #[account(address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the referral tiers
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl UpdateReferralTiers<'_> {
+    ///
+    /// The shares are fractions of the referral fee paid to the 2nd and 3rd tiers
+    ///
+    pub fn invoke(
+        ctx: Context<UpdateReferralTiers>,
+        referral_tier_shares: [u64; MAX_REFERRAL_TIERS - 1],
+    ) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        if !PlatformConfig::is_valid_referral_tier_shares(&referral_tier_shares) {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        let old_referral_tier_shares = platform_config.referral_tier_shares;
+
+        platform_config.referral_tier_shares = referral_tier_shares;
+        platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdateReferralTiersEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            old_referral_tier_shares,
+            referral_tier_shares,
+            updated_at: platform_config.updated_at
+        });
+
+        Ok(())
+    }
+}
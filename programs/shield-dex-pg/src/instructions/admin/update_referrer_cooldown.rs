@@ -0,0 +1,55 @@
+use crate::constants::MAX_REFERRER_COOLDOWN;
+use crate::errors::ErrorCode;
+use crate::schema::platform_config::PlatformConfig;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateReferrerCooldownEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_referrer_cooldown: i64,
+    pub referrer_cooldown: i64,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateReferrerCooldown<'info> {
+    /// Only admin can change the referrer cooldown
+    #[account(address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the referrer cooldown
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl UpdateReferrerCooldown<'_> {
+    pub fn invoke(ctx: Context<UpdateReferrerCooldown>, referrer_cooldown: i64) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        if !(0..=MAX_REFERRER_COOLDOWN).contains(&referrer_cooldown) {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        let old_referrer_cooldown = platform_config.referrer_cooldown;
+
+        platform_config.referrer_cooldown = referrer_cooldown;
+        platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdateReferrerCooldownEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            old_referrer_cooldown,
+            referrer_cooldown,
+            updated_at: platform_config.updated_at
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use crate::constants::{MAX_TIMELOCK_DELAY, MIN_TIMELOCK_DELAY};
+use crate::errors::ErrorCode;
+use crate::schema::{
+    pending_change::{ParamChange, PendingChange},
+    platform_config::PlatformConfig,
+    program_state::ProgramState,
+};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateTimelockDelayEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_timelock_delay: i64,
+    pub timelock_delay: i64,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateTimelockDelay<'info> {
+    /// Only admin can change the timelock delay
+    #[account(mut, address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the timelock delay
+    pub platform_config: Account<'info, PlatformConfig>,
+
+    /// Pending change
+    #[account(
+        init,
+        payer = owner,
+        space = PendingChange::LEN,
+        seeds = [
+            "pending_change".as_bytes(),
+            &platform_config.key().to_bytes(),
+            &[ParamChange::TIMELOCK_DELAY]
+        ],
+        bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl UpdateTimelockDelay<'_> {
+    ///
+    /// The delay is itself timelocked by the current delay
+    ///
+    pub fn invoke(ctx: Context<UpdateTimelockDelay>, timelock_delay: i64, eta: i64) -> Result<()> {
+        let platform_config = &ctx.accounts.platform_config;
+
+        if !(MIN_TIMELOCK_DELAY..=MAX_TIMELOCK_DELAY).contains(&timelock_delay) {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if !platform_config.is_valid_eta(eta, Clock::get()?.unix_timestamp) {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        let event = PendingChange::propose(
+            &mut ctx.accounts.pending_change,
+            platform_config.key(),
+            ctx.accounts.owner.key(),
+            ParamChange::TimelockDelay(timelock_delay),
+            eta,
+        )?;
+        emit_cpi!(event);
+
+        Ok(())
+    }
+}
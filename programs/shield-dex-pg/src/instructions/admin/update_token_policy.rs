@@ -0,0 +1,50 @@
+use crate::errors::ErrorCode;
+use crate::schema::platform_config::PlatformConfig;
+use crate::schema::program_state::ProgramState;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateTokenPolicyEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub old_strict_token_list: bool,
+    pub strict_token_list: bool,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateTokenPolicy<'info> {
+    /// Only admin can change the token policy
+    #[account(address = program_state.admin @ ErrorCode::Unauthorized)]
+    pub owner: Signer<'info>,
+
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+
+    /// Platform config account stores the token policy
+    #[account(mut)]
+    pub platform_config: Account<'info, PlatformConfig>,
+}
+
+impl UpdateTokenPolicy<'_> {
+    pub fn invoke(ctx: Context<UpdateTokenPolicy>, strict_token_list: bool) -> Result<()> {
+        let platform_config = &mut ctx.accounts.platform_config;
+
+        let old_strict_token_list = platform_config.strict_token_list;
+
+        platform_config.strict_token_list = strict_token_list;
+        platform_config.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdateTokenPolicyEvent {
+            authority: ctx.accounts.owner.key(),
+            platform_config: platform_config.key(),
+            old_strict_token_list,
+            strict_token_list,
+            updated_at: platform_config.updated_at
+        });
+
+        Ok(())
+    }
+}
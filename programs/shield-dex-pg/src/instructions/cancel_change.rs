@@ -0,0 +1,69 @@
+use crate::errors::ErrorCode;
+use crate::schema::{
+    pending_change::{ParamChange, PendingChange},
+    pool::Pool,
+    program_state::ProgramState,
+};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct CancelChangeEvent {
+    pub authority: Pubkey,
+    pub proposer: Pubkey,
+    pub target: Pubkey,
+    pub pending_change: Pubkey,
+    pub change: ParamChange,
+    pub canceled_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelChange<'info> {
+    /// The proposer, the admin or the authority of the target pool
+    pub authority: Signer<'info>,
+    /// CHECK: The proposer receives the rent back
+    #[account(mut, address = pending_change.proposer @ ErrorCode::Unauthorized)]
+    pub proposer: AccountInfo<'info>,
+    /// Pending change
+    #[account(mut, close = proposer)]
+    pub pending_change: Account<'info, PendingChange>,
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    /// Pool, only for pool changes canceled by its authority
+    #[account(address = pending_change.target @ ErrorCode::UnmatchPool)]
+    pub pool: Option<AccountLoader<'info, Pool>>,
+}
+
+impl CancelChange<'_> {
+    ///
+    /// The proposer may lose the role it proposed the change with, so the pool authority
+    /// and the admin can also free the slot of the change
+    ///
+    pub fn invoke(ctx: Context<CancelChange>) -> Result<()> {
+        let pending_change = &ctx.accounts.pending_change;
+        let authority = ctx.accounts.authority.key();
+
+        let is_pool_authority = match ctx.accounts.pool.as_ref() {
+            Some(pool) => pool.load()?.authority == authority,
+            None => false,
+        };
+        if authority != pending_change.proposer
+            && authority != ctx.accounts.program_state.admin
+            && !is_pool_authority
+        {
+            return err!(ErrorCode::Unauthorized);
+        }
+
+        emit_cpi!(CancelChangeEvent {
+            authority,
+            proposer: pending_change.proposer,
+            target: pending_change.target,
+            pending_change: pending_change.key(),
+            change: pending_change.change,
+            canceled_at: Clock::get()?.unix_timestamp
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+use crate::constants::{PAUSE_WITHDRAW, ROLE_POOL_MANAGER};
+use crate::errors::ErrorCode;
+use crate::schema::{access_control::AccessControl, pool::*, program_state::ProgramState};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct CancelPoolEvent {
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CancelPool<'info> {
+    /// Only admin, owner or pool manager can cancel the pool
+    #[account(
+      constraint = (authority.key() == program_state.admin
+        || pool.load()?.is_granted(access_control.as_deref(), authority.key(), ROLE_POOL_MANAGER)) @ ErrorCode::Unauthorized
+    )]
+    pub authority: Signer<'info>,
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    /// Pool
+    #[account(mut)]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Access control
+    #[account(
+      has_one = pool @ ErrorCode::UnmatchPool,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Option<Account<'info, AccessControl>>,
+}
+
+impl CancelPool<'_> {
+    pub fn invoke(ctx: Context<CancelPool>) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+
+        if pool.state() != PoolState::Initialized {
+            return err!(ErrorCode::InvalidState);
+        }
+
+        // The pool becomes withdraw-only
+        // A canceled pool can no longer be resumed, so withdrawals cannot stay paused
+        pool.set_state(PoolState::Canceled);
+        pool.pause_flags &= !PAUSE_WITHDRAW;
+        pool.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(CancelPoolEvent {
+            authority: ctx.accounts.authority.key(),
+            pool: ctx.accounts.pool.key(),
+            updated_at: pool.updated_at
+        });
+
+        Ok(())
+    }
+}
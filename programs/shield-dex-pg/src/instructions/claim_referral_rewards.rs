@@ -0,0 +1,91 @@
+use crate::{errors::ErrorCode, schema::referral_rewards::ReferralRewards};
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token, token_interface};
+
+#[event]
+pub struct ClaimReferralRewardsEvent {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub total_claimed: u64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimReferralRewards<'info> {
+    /// Referrer owning the rewards
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// Mint of the rewards
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    /// Balance of the referrer in the mint
+    #[account(
+      mut,
+      seeds = ["referral_rewards".as_bytes(), &owner.key().to_bytes(), &mint.key().to_bytes()],
+      bump = referral_rewards.bump
+    )]
+    pub referral_rewards: Account<'info, ReferralRewards>,
+    /// CHECK: The referral vault holding the unclaimed rewards
+    #[account(seeds = ["referral_vault".as_bytes()], bump)]
+    pub referral_vault: AccountInfo<'info>,
+    #[account(
+      mut,
+      associated_token::mint = mint,
+      associated_token::authority = referral_vault,
+      associated_token::token_program = token_program
+    )]
+    pub referral_treasury: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+      init_if_needed,
+      payer = owner,
+      associated_token::mint = mint,
+      associated_token::authority = owner,
+      associated_token::token_program = token_program
+    )]
+    pub dst: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// System programs
+    /// Token program of the mint, SPL Token or Token-2022
+    pub token_program: Interface<'info, token_interface::TokenInterface>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl ClaimReferralRewards<'_> {
+    pub fn invoke(ctx: Context<ClaimReferralRewards>) -> Result<u64> {
+        let referral_rewards = &mut ctx.accounts.referral_rewards;
+        let seeds: &[&[&[u8]]] = &[&["referral_vault".as_ref(), &[ctx.bumps.referral_vault]]];
+
+        if referral_rewards.accrued == 0 {
+            return err!(ErrorCode::NothingToClaim);
+        }
+
+        let amount = referral_rewards
+            .claim(Clock::get()?.unix_timestamp)
+            .ok_or(ErrorCode::Overflow)?;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.referral_treasury.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.dst.to_account_info(),
+                    authority: ctx.accounts.referral_vault.to_account_info(),
+                },
+                seeds,
+            ),
+            amount,
+            ctx.accounts.mint.decimals,
+        )?;
+
+        emit_cpi!(ClaimReferralRewardsEvent {
+            owner: ctx.accounts.owner.key(),
+            mint: ctx.accounts.mint.key(),
+            amount,
+            total_claimed: referral_rewards.total_claimed,
+        });
+
+        Ok(amount)
+    }
+}
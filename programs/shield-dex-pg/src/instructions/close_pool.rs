@@ -0,0 +1,244 @@
+use crate::{
+    errors::ErrorCode,
+    schema::{
+        pending_change::ParamChange,
+        pool::{Pool, PoolState},
+        pool_index::PoolIndex,
+    },
+    utils::close_account,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{associated_token, token_2022, token_interface};
+
+#[event]
+pub struct ClosePoolEvent {
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub a: u64,
+    pub b: u64,
+    pub closed_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index_page_a: u32, index_page_b: u32)]
+pub struct ClosePool<'info> {
+    /// Authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Pool
+    #[account(
+    mut,
+    close = authority,
+    has_one = authority @ ErrorCode::Unauthorized,
+    has_one = mint_a @ ErrorCode::UnmatchPool,
+    has_one = mint_b @ ErrorCode::UnmatchPool,
+    has_one = lp_mint @ ErrorCode::UnmatchPool
+  )]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Mint A
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(
+    mut,
+    associated_token::mint = mint_a,
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program_a
+  )]
+    pub treasury_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+    init_if_needed,
+    payer = authority,
+    associated_token::mint = mint_a,
+    associated_token::authority = authority,
+    associated_token::token_program = token_program_a
+  )]
+    pub dst_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// Mint B
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    #[account(
+    mut,
+    associated_token::mint = mint_b,
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program_b
+  )]
+    pub treasury_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    #[account(
+    init_if_needed,
+    payer = authority,
+    associated_token::mint = mint_b,
+    associated_token::authority = authority,
+    associated_token::token_program = token_program_b
+  )]
+    pub dst_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    // LP Mint
+    #[account(mut, mint::token_program = lp_token_program)]
+    pub lp_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    /// CHECK: The pool escrow
+    #[account(seeds = ["escrow".as_bytes(), &pool.key().to_bytes()], bump)]
+    pub escrow: AccountInfo<'info>,
+    /// CHECK: Access control of the pool, possibly uninitialized
+    #[account(mut, seeds = ["access_control".as_bytes(), &pool.key().to_bytes()], bump)]
+    pub access_control: AccountInfo<'info>,
+    /// Pool index page of mint A listing the pool
+    #[account(
+      mut,
+      seeds = ["pool_index".as_bytes(), &mint_a.key().to_bytes(), &index_page_a.to_le_bytes()],
+      bump
+    )]
+    pub pool_index_a: Box<Account<'info, PoolIndex>>,
+    /// Pool index page of mint B listing the pool
+    #[account(
+      mut,
+      seeds = ["pool_index".as_bytes(), &mint_b.key().to_bytes(), &index_page_b.to_le_bytes()],
+      bump
+    )]
+    pub pool_index_b: Box<Account<'info, PoolIndex>>,
+    /// CHECK: Pending LP fee change of the pool, which must have been canceled
+    #[account(
+      seeds = ["pending_change".as_bytes(), &pool.key().to_bytes(), &[ParamChange::LP_FEE]],
+      bump
+    )]
+    pub pending_lp_fee_change: AccountInfo<'info>,
+    /// CHECK: Pending referral fee change of the pool, which must have been canceled
+    #[account(
+      seeds = ["pending_change".as_bytes(), &pool.key().to_bytes(), &[ParamChange::REFERRAL_FEE]],
+      bump
+    )]
+    pub pending_referral_fee_change: AccountInfo<'info>,
+    /// System programs
+    /// Token program of mint A, SPL Token or Token-2022
+    pub token_program_a: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of mint B, SPL Token or Token-2022
+    pub token_program_b: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of the LP mint, Token-2022 for pools created with metadata
+    pub lp_token_program: Interface<'info, token_interface::TokenInterface>,
+    pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl ClosePool<'_> {
+    pub fn invoke(ctx: Context<ClosePool>, _index_page_a: u32, _index_page_b: u32) -> Result<()> {
+        let pool = ctx.accounts.pool.load()?;
+        let seeds: &[&[&[u8]]] = &[&[
+            "escrow".as_ref(),
+            &ctx.accounts.pool.key().to_bytes(),
+            &[ctx.bumps.escrow],
+        ]];
+
+        if pool.state() != PoolState::Canceled {
+            return err!(ErrorCode::InvalidState);
+        }
+        // Every LP must have withdrawn their liquidity
+        if ctx.accounts.lp_mint.supply != 0 {
+            return err!(ErrorCode::InvalidState);
+        }
+        // A pending change left open would apply to a new pool of the pair at the same address
+        if ctx.accounts.pending_lp_fee_change.owner == &crate::ID
+            || ctx.accounts.pending_referral_fee_change.owner == &crate::ID
+        {
+            return err!(ErrorCode::PendingChangeOpen);
+        }
+
+        // Sweep the remaining fees and dust to the authority
+        let a = ctx.accounts.treasury_a.amount;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_a.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.treasury_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
+                    to: ctx.accounts.dst_a.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                seeds,
+            ),
+            a,
+            ctx.accounts.mint_a.decimals,
+        )?;
+        let b = ctx.accounts.treasury_b.amount;
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.token_program_b.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.treasury_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
+                    to: ctx.accounts.dst_b.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                seeds,
+            ),
+            b,
+            ctx.accounts.mint_b.decimals,
+        )?;
+
+        // Close the treasuries and return their rent
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program_a.to_account_info(),
+            token_interface::CloseAccount {
+                account: ctx.accounts.treasury_a.to_account_info(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            seeds,
+        ))?;
+        token_interface::close_account(CpiContext::new_with_signer(
+            ctx.accounts.token_program_b.to_account_info(),
+            token_interface::CloseAccount {
+                account: ctx.accounts.treasury_b.to_account_info(),
+                destination: ctx.accounts.authority.to_account_info(),
+                authority: ctx.accounts.escrow.to_account_info(),
+            },
+            seeds,
+        ))?;
+
+        // Close the LP mint so that the pair can be pooled again in the config
+        // Pools migrated from the baseline keep their SPL Token LP mint
+        if ctx.accounts.lp_token_program.key() == token_2022::ID {
+            token_interface::close_account(CpiContext::new_with_signer(
+                ctx.accounts.lp_token_program.to_account_info(),
+                token_interface::CloseAccount {
+                    account: ctx.accounts.lp_mint.to_account_info(),
+                    destination: ctx.accounts.authority.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                seeds,
+            ))?;
+        }
+        // Drop the roles granted in the pool, which a new pool of the pair would inherit
+        if ctx.accounts.access_control.owner == &crate::ID {
+            close_account(
+                &ctx.accounts.access_control,
+                &ctx.accounts.authority.to_account_info(),
+            )?;
+        }
+
+        // Unlist the pool
+        let authority = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        PoolIndex::remove(
+            &mut ctx.accounts.pool_index_a,
+            ctx.accounts.pool.key(),
+            &authority,
+            &system_program,
+        )?;
+        PoolIndex::remove(
+            &mut ctx.accounts.pool_index_b,
+            ctx.accounts.pool.key(),
+            &authority,
+            &system_program,
+        )?;
+
+        emit_cpi!(ClosePoolEvent {
+            authority: ctx.accounts.authority.key(),
+            pool: ctx.accounts.pool.key(),
+            a,
+            b,
+            closed_at: Clock::get()?.unix_timestamp
+        });
+
+        Ok(())
+    }
+}
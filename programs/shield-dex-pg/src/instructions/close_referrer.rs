@@ -0,0 +1,77 @@
+use crate::errors::ErrorCode;
+use crate::schema::{
+    platform_config::PlatformConfig, referer::Referrer, referral_stats::ReferralStats,
+};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct CloseReferrerEvent {
+    pub referee: Pubkey,
+    pub referrer: Pubkey,
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub closed_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CloseReferrer<'info> {
+    /// Referee, receiving the rent
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: Pool of a pool-scoped binding, possibly closed, none for a global binding
+    #[account(address = referrer.pool @ ErrorCode::UnmatchPool)]
+    pub pool: Option<UncheckedAccount<'info>>,
+    /// Config holding the referrer cooldown
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// Referrer
+    #[account(
+      mut,
+      close = authority,
+      constraint = referrer.referee == authority.key() @ ErrorCode::Unauthorized
+    )]
+    pub referrer: Account<'info, Referrer>,
+    /// CHECK: Stats of the referrer, uncounting the referee of a bound binding
+    #[account(
+      mut,
+      seeds = ["referral_stats".as_bytes(), &referrer.owner.to_bytes()],
+      bump
+    )]
+    pub referral_stats: AccountInfo<'info>,
+}
+
+impl CloseReferrer<'_> {
+    ///
+    /// Closing then creating a binding is a rebind, so it waits for the cooldown too
+    ///
+    pub fn invoke(ctx: Context<CloseReferrer>) -> Result<()> {
+        let platform_config = &ctx.accounts.platform_config;
+        let referrer = &ctx.accounts.referrer;
+        let now = Clock::get()?.unix_timestamp;
+        let pool_config = ctx
+            .accounts
+            .referrer
+            .pool_config(ctx.accounts.pool.as_deref())?;
+
+        if !referrer.is_cooldown_config(pool_config, platform_config) {
+            return err!(ErrorCode::InvalidPlatformConfig);
+        }
+        if !referrer.is_cooled_down(platform_config.referrer_cooldown, now) {
+            return err!(ErrorCode::ReferrerCooldown);
+        }
+        // An unbound binding was already uncounted
+        if referrer.is_bound() {
+            ReferralStats::remove_referee_from(&ctx.accounts.referral_stats, now)?;
+        }
+
+        emit_cpi!(CloseReferrerEvent {
+            referee: ctx.accounts.authority.key(),
+            referrer: referrer.key(),
+            pool: referrer.pool,
+            owner: referrer.owner,
+            closed_at: now,
+        });
+
+        Ok(())
+    }
+}
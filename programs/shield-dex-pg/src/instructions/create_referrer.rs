@@ -1,24 +1,59 @@
-use crate::schema::{pool::Pool, referer::Referrer};
+use crate::constants::REFERRER_VERSION;
+use crate::errors::ErrorCode;
+use crate::schema::{pool::Pool, referer::Referrer, referral_stats::ReferralStats};
 use anchor_lang::prelude::*;
 
 #[event]
 pub struct CreateReferrerEvent {
     pub owner: Pubkey,
-    // pub pool: Pubkey,
+    pub pool: Pubkey,
     pub referee: Pubkey,
+    pub parent: Pubkey,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(referer_address: Pubkey)]
 pub struct CreateReferrer<'info> {
     /// Authority
     #[account(mut)]
     pub authority: Signer<'info>,
-
-    #[account(mut)]
-    pub pool: Account<'info, Pool>,
+    /// Pool scoping the binding, none for a global binding
+    pub pool: Option<AccountLoader<'info, Pool>>,
     // Referrer
-    #[account(init, payer = authority,seeds = ["referrer".as_bytes(), &authority.key().to_bytes() ], space = Referrer::LEN, bump)]
+    #[account(
+      init,
+      payer = authority,
+      seeds = [
+        "referrer".as_bytes(),
+        &Referrer::scope(pool.as_ref().map(|pool| pool.key())).to_bytes(),
+        &authority.key().to_bytes()
+      ],
+      space = Referrer::LEN,
+      bump
+    )]
     pub referrer: Account<'info, Referrer>,
+    /// CHECK: Binding of the referrer itself in the same scope, empty at the top tier
+    /// A self referral would alias the binding being created, so it is rejected first
+    #[account(
+      seeds = [
+        "referrer".as_bytes(),
+        &Referrer::scope(pool.as_ref().map(|pool| pool.key())).to_bytes(),
+        &referer_address.to_bytes()
+      ],
+      bump,
+      constraint = referer_address != authority.key() @ ErrorCode::ReferralCycle
+    )]
+    pub parent: AccountInfo<'info>,
+    /// Stats of the referrer, counting its referees
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = ReferralStats::space(0),
+      seeds = ["referral_stats".as_bytes(), &referer_address.to_bytes()],
+      bump
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
 
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -26,22 +61,61 @@ pub struct CreateReferrer<'info> {
 
 impl CreateReferrer<'_> {
     pub fn invoke(ctx: Context<CreateReferrer>, referer_address: Pubkey) -> Result<()> {
-        let referrer = &mut ctx.accounts.referrer;
-        msg!(
-            "referrer: system_program: {}",
-            ctx.accounts.system_program.key()
-        );
-
-        referrer.owner = referer_address;
-        // referrer.pool = ctx.accounts.pool.key();
-        referrer.referee = ctx.accounts.authority.key();
-
-        emit!(CreateReferrerEvent {
-            owner: referer_address,
-            // pool: ctx.accounts.pool.key(),
-            referee: ctx.accounts.authority.key(),
-        });
+        let event = bind_referrer(
+            &mut ctx.accounts.referrer,
+            &mut ctx.accounts.referral_stats,
+            ctx.bumps.referral_stats,
+            &ctx.accounts.parent,
+            ctx.remaining_accounts,
+            referer_address,
+            ctx.accounts.authority.key(),
+            Referrer::scope(ctx.accounts.pool.as_ref().map(|pool| pool.key())),
+        )?;
+
+        emit_cpi!(event);
 
         Ok(())
     }
 }
+
+///
+/// Bind the referee to `owner` in the scope `pool`, shared by `create_referrer` and `create_referrer_by_code`
+/// The uplines are the bindings above `parent` within the paid tiers,
+/// checked so that the referee never earns from its own swaps
+///
+#[allow(clippy::too_many_arguments)]
+pub fn bind_referrer(
+    referrer: &mut Account<Referrer>,
+    referral_stats: &mut Account<ReferralStats>,
+    referral_stats_bump: u8,
+    parent: &AccountInfo,
+    uplines: &[AccountInfo],
+    owner: Pubkey,
+    referee: Pubkey,
+    pool: Pubkey,
+) -> Result<CreateReferrerEvent> {
+    let now = Clock::get()?.unix_timestamp;
+    let upline = Referrer::load_parent(parent)?;
+
+    Referrer::check_cycle(owner, referee, upline.as_ref(), uplines)?;
+
+    if !referral_stats.is_initialized() {
+        referral_stats.owner = owner;
+        referral_stats.bump = referral_stats_bump;
+    }
+    referral_stats.add_referee(now).ok_or(ErrorCode::Overflow)?;
+
+    referrer.owner = owner;
+    referrer.pool = pool;
+    referrer.referee = referee;
+    referrer.version = REFERRER_VERSION;
+    referrer.bound_at = now;
+    referrer.parent = upline.map(|_| parent.key()).unwrap_or_default();
+
+    Ok(CreateReferrerEvent {
+        owner,
+        pool,
+        referee,
+        parent: referrer.parent,
+    })
+}
@@ -0,0 +1,82 @@
+use crate::errors::ErrorCode;
+use crate::instructions::create_referrer::bind_referrer;
+use crate::schema::{
+    pool::Pool, referer::Referrer, referral_code::ReferralCode, referral_stats::ReferralStats,
+};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct CreateReferrerByCode<'info> {
+    /// Authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Pool scoping the binding, none for a global binding
+    pub pool: Option<AccountLoader<'info, Pool>>,
+    /// Referral code of the referrer
+    #[account(
+      seeds = ["referral_code".as_bytes(), ReferralCode::seed(&code)?],
+      bump = referral_code.bump
+    )]
+    pub referral_code: Account<'info, ReferralCode>,
+    // Referrer
+    #[account(
+      init,
+      payer = authority,
+      seeds = [
+        "referrer".as_bytes(),
+        &Referrer::scope(pool.as_ref().map(|pool| pool.key())).to_bytes(),
+        &authority.key().to_bytes()
+      ],
+      space = Referrer::LEN,
+      bump
+    )]
+    pub referrer: Account<'info, Referrer>,
+    /// CHECK: Binding of the referrer itself in the same scope, empty at the top tier
+    /// A self referral would alias the binding being created, so it is rejected first
+    #[account(
+      seeds = [
+        "referrer".as_bytes(),
+        &Referrer::scope(pool.as_ref().map(|pool| pool.key())).to_bytes(),
+        &referral_code.owner.to_bytes()
+      ],
+      bump,
+      constraint = referral_code.owner != authority.key() @ ErrorCode::ReferralCycle
+    )]
+    pub parent: AccountInfo<'info>,
+    /// Stats of the referrer, counting its referees
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = ReferralStats::space(0),
+      seeds = ["referral_stats".as_bytes(), &referral_code.owner.to_bytes()],
+      bump
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl CreateReferrerByCode<'_> {
+    ///
+    /// Same as `create_referrer`, with the referrer resolved from its code
+    ///
+    pub fn invoke(ctx: Context<CreateReferrerByCode>, _code: String) -> Result<()> {
+        let event = bind_referrer(
+            &mut ctx.accounts.referrer,
+            &mut ctx.accounts.referral_stats,
+            ctx.bumps.referral_stats,
+            &ctx.accounts.parent,
+            ctx.remaining_accounts,
+            ctx.accounts.referral_code.owner,
+            ctx.accounts.authority.key(),
+            Referrer::scope(ctx.accounts.pool.as_ref().map(|pool| pool.key())),
+        )?;
+
+        emit_cpi!(event);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,191 @@
+use crate::constants::{ROLE_FEE_MANAGER, ROLE_REFERRAL_MANAGER};
+use crate::errors::ErrorCode;
+use crate::instructions::{
+    admin::{
+        update_platform_config::UpdatePlatformConfigEvent,
+        update_timelock_delay::UpdateTimelockDelayEvent,
+    },
+    update_lp_fee::UpdateLPFeeEvent,
+    update_referral_fee::UpdateReferralFeeEvent,
+    update_tax::UpdateTaxEvent,
+};
+use crate::schema::{
+    access_control::AccessControl,
+    pending_change::{ParamChange, PendingChange},
+    platform_config::PlatformConfig,
+    pool::*,
+    program_state::ProgramState,
+};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ExecuteChangeEvent {
+    pub executor: Pubkey,
+    pub target: Pubkey,
+    pub pending_change: Pubkey,
+    pub change: ParamChange,
+    pub executed_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> {
+    /// Anyone can execute a change once its timelock elapsed
+    pub executor: Signer<'info>,
+    /// CHECK: The proposer receives the rent back
+    #[account(mut, address = pending_change.proposer @ ErrorCode::Unauthorized)]
+    pub proposer: AccountInfo<'info>,
+    /// Pending change
+    #[account(mut, close = proposer)]
+    pub pending_change: Account<'info, PendingChange>,
+    /// Program state stores the admin, the proposer of the platform changes
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    /// Which config the change targets or the pool belongs to.
+    #[account(mut)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
+    /// Pool, only for pool changes
+    #[account(mut, constraint = pool.load()?.tax == platform_config.key() @ ErrorCode::InvalidPlatformConfig)]
+    pub pool: Option<AccountLoader<'info, Pool>>,
+    /// Access control of the pool, for changes proposed by a role holder
+    pub access_control: Option<Account<'info, AccessControl>>,
+}
+
+impl ExecuteChange<'_> {
+    pub fn invoke(ctx: Context<ExecuteChange>) -> Result<()> {
+        let pending_change = &ctx.accounts.pending_change;
+        let admin = ctx.accounts.program_state.admin;
+        let platform_config = &mut ctx.accounts.platform_config;
+        let now = Clock::get()?.unix_timestamp;
+
+        if now < pending_change.eta {
+            return err!(ErrorCode::TimelockNotElapsed);
+        }
+        let expected_target = match pending_change.change.is_pool_change() {
+            true => ctx
+                .accounts
+                .pool
+                .as_ref()
+                .ok_or(ErrorCode::UnmatchPool)?
+                .key(),
+            false => platform_config.key(),
+        };
+        if pending_change.target != expected_target {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if let Some(access_control) = ctx.accounts.access_control.as_ref() {
+            if access_control.pool != expected_target {
+                return err!(ErrorCode::UnmatchPool);
+            }
+        }
+
+        // The tier and the roles may have changed since the proposal, so they are checked again
+        let access_control = ctx.accounts.access_control.as_deref();
+        match pending_change.change {
+            ParamChange::LpFee(lp_fee) => {
+                if !platform_config.is_valid_lp_fee(lp_fee) {
+                    return err!(ErrorCode::InvalidParams);
+                }
+                let pool_loader = ctx.accounts.pool.as_ref().ok_or(ErrorCode::UnmatchPool)?;
+                let mut pool = pool_loader.load_mut()?;
+                if !pool.is_granted(access_control, pending_change.proposer, ROLE_FEE_MANAGER) {
+                    return err!(ErrorCode::Unauthorized);
+                }
+                let old_lp_fee = pool.lp_fee;
+                pool.lp_fee = lp_fee;
+                pool.updated_at = now;
+
+                emit_cpi!(UpdateLPFeeEvent {
+                    authority: pending_change.proposer,
+                    pool: pool_loader.key(),
+                    old_lp_fee,
+                    lp_fee,
+                    updated_at: pool.updated_at
+                });
+            }
+            ParamChange::ReferralFee(referral_fee) => {
+                if !platform_config.is_valid_referral_fee(referral_fee) {
+                    return err!(ErrorCode::InvalidParams);
+                }
+                let pool_loader = ctx.accounts.pool.as_ref().ok_or(ErrorCode::UnmatchPool)?;
+                let mut pool = pool_loader.load_mut()?;
+                if !pool.is_granted(
+                    access_control,
+                    pending_change.proposer,
+                    ROLE_REFERRAL_MANAGER,
+                ) {
+                    return err!(ErrorCode::Unauthorized);
+                }
+                let old_referral_fee = pool.referral_fee;
+                pool.referral_fee = referral_fee;
+                pool.updated_at = now;
+
+                emit_cpi!(UpdateReferralFeeEvent {
+                    authority: pending_change.proposer,
+                    pool: pool_loader.key(),
+                    old_referral_fee,
+                    referral_fee,
+                    updated_at: pool.updated_at
+                });
+            }
+            ParamChange::FeeTier(fee_tier) => {
+                if pending_change.proposer != admin {
+                    return err!(ErrorCode::Unauthorized);
+                }
+                let old_fee_tier = platform_config.fee_tier();
+                platform_config.set_fee_tier(&fee_tier);
+                platform_config.updated_at = now;
+
+                emit_cpi!(UpdatePlatformConfigEvent {
+                    authority: pending_change.proposer,
+                    platform_config: platform_config.key(),
+                    old_fee_tier,
+                    fee_tier,
+                    updated_at: platform_config.updated_at
+                });
+            }
+            ParamChange::Tax(tax) => {
+                if pending_change.proposer != admin {
+                    return err!(ErrorCode::Unauthorized);
+                }
+                let old_tax = platform_config.tax;
+                platform_config.tax = tax;
+                platform_config.updated_at = now;
+
+                emit_cpi!(UpdateTaxEvent {
+                    authority: pending_change.proposer,
+                    platform_config: platform_config.key(),
+                    old_tax,
+                    new_tax: tax,
+                    updated_at: platform_config.updated_at
+                });
+            }
+            ParamChange::TimelockDelay(timelock_delay) => {
+                if pending_change.proposer != admin {
+                    return err!(ErrorCode::Unauthorized);
+                }
+                let old_timelock_delay = platform_config.timelock_delay;
+                platform_config.timelock_delay = timelock_delay;
+                platform_config.updated_at = now;
+
+                emit_cpi!(UpdateTimelockDelayEvent {
+                    authority: pending_change.proposer,
+                    platform_config: platform_config.key(),
+                    old_timelock_delay,
+                    timelock_delay,
+                    updated_at: platform_config.updated_at
+                });
+            }
+        }
+
+        emit_cpi!(ExecuteChangeEvent {
+            executor: ctx.accounts.executor.key(),
+            target: pending_change.target,
+            pending_change: pending_change.key(),
+            change: pending_change.change,
+            executed_at: now
+        });
+
+        Ok(())
+    }
+}
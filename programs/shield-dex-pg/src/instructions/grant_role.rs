@@ -0,0 +1,60 @@
+use crate::constants::ROLE_ALL;
+use crate::errors::ErrorCode;
+use crate::schema::{access_control::AccessControl, pool::*};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct GrantRoleEvent {
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub grantee: Pubkey,
+    pub roles: u8,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    /// Authority
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Pool
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Access control
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = AccessControl::LEN,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Account<'info, AccessControl>,
+    /// System programs
+    pub system_program: Program<'info, System>,
+}
+
+impl GrantRole<'_> {
+    pub fn invoke(ctx: Context<GrantRole>, grantee: Pubkey, roles: u8) -> Result<()> {
+        let access_control = &mut ctx.accounts.access_control;
+
+        if roles == 0 || roles & !ROLE_ALL != 0 {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        access_control.pool = ctx.accounts.pool.key();
+        let roles = access_control
+            .grant(grantee, roles)
+            .ok_or(ErrorCode::TooManyRoleGrants)?;
+
+        emit_cpi!(GrantRoleEvent {
+            authority: ctx.accounts.authority.key(),
+            pool: ctx.accounts.pool.key(),
+            grantee,
+            roles,
+            updated_at: Clock::get()?.unix_timestamp
+        });
+
+        Ok(())
+    }
+}
@@ -1,22 +1,25 @@
 use crate::{
-    constants::{CUSTOMED_FEE_BOUND, LP_MINT_DECIMALS, MAXIMUM_FEE},
+    constants::{CURVE_CONSTANT_PRODUCT, LP_MINT_SYMBOL, POOL_VERSION},
     errors::ErrorCode,
     schema::{
         platform_config::PlatformConfig,
         pool::{Pool, PoolState},
+        pool_index::PoolIndex,
+        program_state::ProgramState,
+        token_registry::TokenRegistry,
     },
+    utils::{create_lp_mint, lp_mint_name, mint_risk_flags, mint_symbol},
 };
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program::system_instruction;
-use anchor_spl::{
-    associated_token,
-    token::{self},
-};
+use anchor_spl::{associated_token, token, token_2022, token_interface};
+use spl_token_metadata_interface::state::TokenMetadata;
 
 #[event]
 pub struct InitializeEvent {
     pub authority: Pubkey,
     pub pool: Pubkey,
+    pub id: u64,
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
     pub lp_mint: Pubkey,
@@ -26,78 +29,159 @@ pub struct InitializeEvent {
     pub referral_fee: u64,
     pub lp_fee: u64,
     pub tax: Pubkey,
+    pub risk_flags: u8,
     pub created_at: i64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(a: u64, b: u64, referral_fee: u64, lp_fee: Option<u64>, index_page_a: u32, index_page_b: u32)]
 pub struct Initialize<'info> {
     /// Authority
     #[account(mut)]
     pub authority: Signer<'info>,
+    /// Program state assigning the pool id
+    #[account(mut, seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Box<Account<'info, ProgramState>>,
     /// Which config the pool belongs to.
     pub platform_config: Box<Account<'info, PlatformConfig>>,
-    /// Pool
-    #[account(init, payer = authority,  space = Pool::LEN)]
-    pub pool: Account<'info, Pool>,
-    /// Mint A
-    pub mint_a: Box<Account<'info, token::Mint>>,
+    /// Pool, canonical for the pair of mints in the platform config
+    #[account(
+      init,
+      payer = authority,
+      space = Pool::LEN,
+      seeds = [
+        "pool".as_bytes(),
+        &platform_config.key().to_bytes(),
+        &mint_a.key().to_bytes(),
+        &mint_b.key().to_bytes()
+      ],
+      bump
+    )]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Mint A, of the SPL Token or Token-2022 program
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
       mut,
       associated_token::mint = mint_a,
-      associated_token::authority = authority
+      associated_token::authority = authority,
+      associated_token::token_program = token_program_a
     )]
-    pub src_a: Box<Account<'info, token::TokenAccount>>,
+    pub src_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
       init_if_needed,
       payer = authority,
       associated_token::mint = mint_a,
-      associated_token::authority = escrow
+      associated_token::authority = escrow,
+      associated_token::token_program = token_program_a
     )]
-    pub treasury_a: Box<Account<'info, token::TokenAccount>>,
-    /// Mint B
-    pub mint_b: Box<Account<'info, token::Mint>>,
+    pub treasury_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// Mint B, of the SPL Token or Token-2022 program
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
       mut,
       associated_token::mint = mint_b,
-      associated_token::authority = authority
+      associated_token::authority = authority,
+      associated_token::token_program = token_program_b
     )]
-    pub src_b: Box<Account<'info, token::TokenAccount>>,
+    pub src_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
       init_if_needed,
       payer = authority,
       associated_token::mint = mint_b,
-      associated_token::authority = escrow
+      associated_token::authority = escrow,
+      associated_token::token_program = token_program_b
     )]
-    pub treasury_b: Box<Account<'info, token::TokenAccount>>,
-    // LP Mint
+    pub treasury_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// CHECK: The Token-2022 LP mint, created with its metadata
+    #[account(mut, seeds = ["lp_mint".as_bytes(), &pool.key().to_bytes()], bump)]
+    pub lp_mint: AccountInfo<'info>,
+    /// CHECK: Metaplex metadata of mint A, naming the LP mint
     #[account(
-      init,
-      payer = authority,
-      mint::decimals = LP_MINT_DECIMALS,
-      mint::authority = escrow,
-      mint::freeze_authority = escrow,
-      seeds = ["lp_mint".as_bytes(), &pool.key().to_bytes()],
+      seeds = ["metadata".as_bytes(), &crate::metaplex::ID.to_bytes(), &mint_a.key().to_bytes()],
+      seeds::program = crate::metaplex::ID,
       bump
     )]
-    pub lp_mint: Box<Account<'info, token::Mint>>,
+    pub metadata_a: Option<AccountInfo<'info>>,
+    /// CHECK: Metaplex metadata of mint B, naming the LP mint
     #[account(
-      init_if_needed,
-      payer = authority,
-      associated_token::mint = lp_mint,
-      associated_token::authority = authority
+      seeds = ["metadata".as_bytes(), &crate::metaplex::ID.to_bytes(), &mint_b.key().to_bytes()],
+      seeds::program = crate::metaplex::ID,
+      bump
     )]
-    pub dst_lp: Box<Account<'info, token::TokenAccount>>,
-    /// CHECK: The pool fee reveiver
+    pub metadata_b: Option<AccountInfo<'info>>,
+    /// CHECK: The LP token account of the authority, created by the associated token program
+    #[account(mut)]
+    pub dst_lp: AccountInfo<'info>,
+    /// CHECK: The fee receiver of the platform config
     #[account(
         mut,
-        address= crate::create_pool_fee_reveiver::id(),
+        address = platform_config.fee_receiver @ ErrorCode::InvalidFeeReceiver,
     )]
     pub taxman: AccountInfo<'info>,
+    /// Token account paying the pool creation fee when it is charged in an SPL token
+    #[account(
+      mut,
+      token::mint = platform_config.create_pool_fee_mint,
+      token::authority = authority
+    )]
+    pub fee_src: Option<Box<Account<'info, token::TokenAccount>>>,
+    /// Token account of the fee receiver collecting the pool creation fee
+    #[account(
+      mut,
+      token::mint = platform_config.create_pool_fee_mint,
+      token::authority = taxman
+    )]
+    pub fee_dst: Option<Box<Account<'info, token::TokenAccount>>>,
     /// CHECK: The pool escrow
     #[account(seeds = ["escrow".as_bytes(), &pool.key().to_bytes()], bump)]
     pub escrow: AccountInfo<'info>,
+    /// CHECK: The registry of mint A, possibly uninitialized
+    #[account(seeds = ["token_registry".as_bytes(), &mint_a.key().to_bytes()], bump)]
+    pub token_registry_a: AccountInfo<'info>,
+    /// CHECK: The registry of mint B, possibly uninitialized
+    #[account(seeds = ["token_registry".as_bytes(), &mint_b.key().to_bytes()], bump)]
+    pub token_registry_b: AccountInfo<'info>,
+    /// Pool index page of mint A
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = PoolIndex::space(0),
+      seeds = ["pool_index".as_bytes(), &mint_a.key().to_bytes(), &index_page_a.to_le_bytes()],
+      bump
+    )]
+    pub pool_index_a: Box<Account<'info, PoolIndex>>,
+    /// Previous pool index page of mint A, required past the first page
+    #[account(
+      seeds = ["pool_index".as_bytes(), &mint_a.key().to_bytes(), &index_page_a.saturating_sub(1).to_le_bytes()],
+      bump
+    )]
+    pub prev_pool_index_a: Option<Box<Account<'info, PoolIndex>>>,
+    /// Pool index page of mint B
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = PoolIndex::space(0),
+      seeds = ["pool_index".as_bytes(), &mint_b.key().to_bytes(), &index_page_b.to_le_bytes()],
+      bump
+    )]
+    pub pool_index_b: Box<Account<'info, PoolIndex>>,
+    /// Previous pool index page of mint B, required past the first page
+    #[account(
+      seeds = ["pool_index".as_bytes(), &mint_b.key().to_bytes(), &index_page_b.saturating_sub(1).to_le_bytes()],
+      bump
+    )]
+    pub prev_pool_index_b: Option<Box<Account<'info, PoolIndex>>>,
     /// System programs
+    /// Token program of the pool creation fee mint
     pub token_program: Program<'info, token::Token>,
+    /// Token program of mint A, SPL Token or Token-2022
+    pub token_program_a: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of mint B, SPL Token or Token-2022
+    pub token_program_b: Interface<'info, token_interface::TokenInterface>,
+    pub token_2022_program: Program<'info, token_2022::Token2022>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -109,80 +193,188 @@ impl Initialize<'_> {
         a: u64,
         b: u64,
         referral_fee: u64,
-        sol_amount_for_custom_fee: u64,
-        lp_fee: u64,
+        lp_fee: Option<u64>,
+        index_page_a: u32,
+        index_page_b: u32,
     ) -> Result<()> {
+        let lp_fee = lp_fee.unwrap_or(ctx.accounts.platform_config.default_lp_fee);
+        let create_pool_fee = ctx
+            .accounts
+            .platform_config
+            .calc_create_pool_fee(lp_fee)
+            .ok_or(ErrorCode::Overflow)?;
         msg!(
-            "Initialize: a={}, b={}, lp_fee={}, tax={}",
+            "Initialize: a={}, b={}, lp_fee={}, create_pool_fee={}",
             a,
             b,
             lp_fee,
-            sol_amount_for_custom_fee,
+            create_pool_fee,
         );
-        let pool = &mut ctx.accounts.pool;
-        let seeds: &[&[&[u8]]] = &[&[
-            "escrow".as_ref(),
-            &pool.key().to_bytes(),
-            &[ctx.bumps.escrow],
-        ]];
+        let pool_key = ctx.accounts.pool.key();
+        let mut pool = ctx.accounts.pool.load_init()?;
+        let seeds: &[&[&[u8]]] = &[&["escrow".as_ref(), &pool_key.to_bytes(), &[ctx.bumps.escrow]]];
 
+        if ctx.accounts.platform_config.emergency_paused {
+            return err!(ErrorCode::EmergencyPaused);
+        }
         if ctx.accounts.mint_a.key() == ctx.accounts.mint_b.key() {
             return err!(ErrorCode::InvalidParams);
         }
+        // Mints are sorted so that a pair has a single pool per config
+        if ctx.accounts.mint_a.key() > ctx.accounts.mint_b.key() {
+            return err!(ErrorCode::UnsortedMints);
+        }
 
-        if a <= 0 || b <= 0 {
+        // Reject denylisted mints, and unlisted ones in strict mode
+        let status_a = TokenRegistry::status_of(&ctx.accounts.token_registry_a)?;
+        let status_b = TokenRegistry::status_of(&ctx.accounts.token_registry_b)?;
+        if !ctx.accounts.platform_config.is_allowed_token(status_a)
+            || !ctx.accounts.platform_config.is_allowed_token(status_b)
+        {
+            return err!(ErrorCode::TokenNotAllowed);
+        }
+
+        // Reject the risks the platform does not tolerate, flag the others on the pool
+        let risk_flags = mint_risk_flags(&ctx.accounts.mint_a.to_account_info())?
+            | mint_risk_flags(&ctx.accounts.mint_b.to_account_info())?;
+        if risk_flags & ctx.accounts.platform_config.rejected_mint_risks != 0 {
+            return err!(ErrorCode::UnsafeMint);
+        }
+
+        if a == 0 || b == 0 {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if !ctx.accounts.platform_config.is_valid_lp_fee(lp_fee) {
             return err!(ErrorCode::InvalidParams);
         }
-        if lp_fee > MAXIMUM_FEE {
+        if !ctx
+            .accounts
+            .platform_config
+            .is_valid_referral_fee(referral_fee)
+        {
             return err!(ErrorCode::InvalidParams);
         }
+        if !ctx
+            .accounts
+            .platform_config
+            .is_allowed_curve(CURVE_CONSTANT_PRODUCT)
+        {
+            return err!(ErrorCode::InvalidPlatformConfig);
+        }
 
-        if lp_fee > CUSTOMED_FEE_BOUND {
-            // Invoke the transfer instruction
-            let ix = system_instruction::transfer(
-                &ctx.accounts.authority.key(),
-                &ctx.accounts.taxman.key(),
-                sol_amount_for_custom_fee,
-            );
-            anchor_lang::solana_program::program::invoke(
-                &ix,
-                &[
-                    ctx.accounts.authority.to_account_info(),
-                    ctx.accounts.taxman.to_account_info(),
-                ],
-            )?;
+        // Charge the pool creation fee configured by the platform
+        if create_pool_fee > 0 {
+            if ctx.accounts.platform_config.is_native_create_pool_fee() {
+                let ix = system_instruction::transfer(
+                    &ctx.accounts.authority.key(),
+                    &ctx.accounts.taxman.key(),
+                    create_pool_fee,
+                );
+                anchor_lang::solana_program::program::invoke(
+                    &ix,
+                    &[
+                        ctx.accounts.authority.to_account_info(),
+                        ctx.accounts.taxman.to_account_info(),
+                    ],
+                )?;
+            } else {
+                let fee_src = ctx
+                    .accounts
+                    .fee_src
+                    .as_ref()
+                    .ok_or(ErrorCode::MissingFeeAccount)?;
+                let fee_dst = ctx
+                    .accounts
+                    .fee_dst
+                    .as_ref()
+                    .ok_or(ErrorCode::MissingFeeAccount)?;
+                token::transfer(
+                    CpiContext::new(
+                        ctx.accounts.token_program.to_account_info(),
+                        token::Transfer {
+                            from: fee_src.to_account_info(),
+                            to: fee_dst.to_account_info(),
+                            authority: ctx.accounts.authority.to_account_info(),
+                        },
+                    ),
+                    create_pool_fee,
+                )?;
+            }
         }
         // Deposit token A
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.token_program_a.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.src_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
                     to: ctx.accounts.treasury_a.to_account_info(),
                     authority: ctx.accounts.authority.to_account_info(),
                 },
             ),
             a,
+            ctx.accounts.mint_a.decimals,
         )?;
         // Deposit token B
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.token_program_b.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.src_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
                     to: ctx.accounts.treasury_b.to_account_info(),
                     authority: ctx.accounts.authority.to_account_info(),
                 },
             ),
             b,
+            ctx.accounts.mint_b.decimals,
+        )?;
+
+        // Create the LP mint with its metadata, and the LP account of the authority
+        let lp_mint_seeds: &[&[u8]] =
+            &["lp_mint".as_ref(), pool_key.as_ref(), &[ctx.bumps.lp_mint]];
+        create_lp_mint(
+            &ctx.accounts.lp_mint,
+            &ctx.accounts.escrow,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            &ctx.accounts.token_2022_program.to_account_info(),
+            lp_mint_seeds,
+            seeds[0],
+            TokenMetadata {
+                name: lp_mint_name(
+                    &mint_symbol(
+                        &ctx.accounts.mint_a.to_account_info(),
+                        ctx.accounts.metadata_a.as_ref(),
+                    )?,
+                    &mint_symbol(
+                        &ctx.accounts.mint_b.to_account_info(),
+                        ctx.accounts.metadata_b.as_ref(),
+                    )?,
+                ),
+                symbol: LP_MINT_SYMBOL.to_string(),
+                ..Default::default()
+            },
         )?;
+        // The LP account outlives a closed pool of the same pair and config
+        associated_token::create_idempotent(CpiContext::new(
+            ctx.accounts.associated_token_program.to_account_info(),
+            associated_token::Create {
+                payer: ctx.accounts.authority.to_account_info(),
+                associated_token: ctx.accounts.dst_lp.to_account_info(),
+                authority: ctx.accounts.authority.to_account_info(),
+                mint: ctx.accounts.lp_mint.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                token_program: ctx.accounts.token_2022_program.to_account_info(),
+            },
+        ))?;
 
         // Mint LP tokens
         let lp = Pool::calc_liquidity(a, b).ok_or(ErrorCode::Overflow)?;
-        token::mint_to(
+        token_2022::mint_to(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::MintTo {
+                ctx.accounts.token_2022_program.to_account_info(),
+                token_2022::MintTo {
                     mint: ctx.accounts.lp_mint.to_account_info(),
                     to: ctx.accounts.dst_lp.to_account_info(),
                     authority: ctx.accounts.escrow.to_account_info(),
@@ -192,6 +384,17 @@ impl Initialize<'_> {
             lp,
         )?;
 
+        // Pools are numbered in creation order
+        let program_state = &mut ctx.accounts.program_state;
+        pool.id = program_state.total_pools;
+        program_state.total_pools = program_state
+            .total_pools
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        program_state.updated_at = Clock::get()?.unix_timestamp;
+
+        pool.bump = ctx.bumps.pool;
+        pool.version = POOL_VERSION;
         pool.authority = ctx.accounts.authority.key();
         pool.lp_mint = ctx.accounts.lp_mint.key();
         pool.mint_a = ctx.accounts.mint_a.key();
@@ -199,13 +402,29 @@ impl Initialize<'_> {
         pool.referral_fee = referral_fee;
         pool.lp_fee = lp_fee;
         pool.tax = ctx.accounts.platform_config.key();
-        pool.state = PoolState::Initialized;
+        pool.set_state(PoolState::Initialized);
+        pool.risk_flags = risk_flags;
         pool.created_at = Clock::get()?.unix_timestamp;
         pool.updated_at = Clock::get()?.unix_timestamp;
 
-        emit!(InitializeEvent {
+        // List the pool under both mints for discovery
+        PoolIndex::check_page(index_page_a, ctx.accounts.prev_pool_index_a.as_deref())?;
+        PoolIndex::check_page(index_page_b, ctx.accounts.prev_pool_index_b.as_deref())?;
+        let authority = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let pool_index_a = &mut ctx.accounts.pool_index_a;
+        pool_index_a.mint = ctx.accounts.mint_a.key();
+        pool_index_a.page = index_page_a;
+        PoolIndex::push(pool_index_a, pool_key, &authority, &system_program)?;
+        let pool_index_b = &mut ctx.accounts.pool_index_b;
+        pool_index_b.mint = ctx.accounts.mint_b.key();
+        pool_index_b.page = index_page_b;
+        PoolIndex::push(pool_index_b, pool_key, &authority, &system_program)?;
+
+        emit_cpi!(InitializeEvent {
             authority: ctx.accounts.authority.key(),
-            pool: pool.key(),
+            pool: pool_key,
+            id: pool.id,
             mint_a: ctx.accounts.mint_a.key(),
             mint_b: ctx.accounts.mint_b.key(),
             lp_mint: ctx.accounts.lp_mint.key(),
@@ -215,6 +434,7 @@ impl Initialize<'_> {
             referral_fee,
             lp_fee,
             tax: ctx.accounts.platform_config.key(),
+            risk_flags,
             created_at: pool.created_at
         });
 
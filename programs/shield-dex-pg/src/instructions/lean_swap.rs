@@ -0,0 +1,217 @@
+use crate::{
+    constants::PAUSE_SWAP,
+    errors::ErrorCode,
+    instructions::swap::SwapEvent,
+    schema::{platform_config::PlatformConfig, pool::Pool, swap_counter::SwapCounter},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface;
+
+///
+/// Swap for integrators counting compute units
+/// The destination accounts must exist, and the mints are only matched against the pool
+/// and their token programs
+///
+#[event_cpi]
+#[derive(Accounts)]
+pub struct LeanSwap<'info> {
+    /// Authority
+    pub authority: Signer<'info>,
+    // AMM config
+    #[account(address = pool.load()?.tax @ ErrorCode::InvalidPlatformConfig)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// Pool
+    #[account(mut)]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Shard of the swap counter the pool counts its swaps in, created by an earlier swap
+    #[account(
+      mut,
+      seeds = ["swap_counter".as_bytes(), &[pool.load()?.swap_counter_shard()]],
+      bump = swap_counter.bump
+    )]
+    pub swap_counter: Account<'info, SwapCounter>,
+    /// Bid Mint, matched against the pool mints
+    #[account(mint::token_program = bid_token_program)]
+    pub bid_mint: InterfaceAccount<'info, token_interface::Mint>,
+    #[account(mut, token::mint = bid_mint, token::token_program = bid_token_program)]
+    pub bid_src: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(
+      mut,
+      associated_token::mint = bid_mint,
+      associated_token::authority = escrow,
+      associated_token::token_program = bid_token_program
+    )]
+    pub bid_treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// Ask Mint, matched against the pool mints
+    #[account(mint::token_program = ask_token_program)]
+    pub ask_mint: InterfaceAccount<'info, token_interface::Mint>,
+    #[account(
+      mut,
+      associated_token::mint = ask_mint,
+      associated_token::authority = escrow,
+      associated_token::token_program = ask_token_program
+    )]
+    pub ask_treasury: InterfaceAccount<'info, token_interface::TokenAccount>,
+    #[account(mut, token::mint = ask_mint, token::token_program = ask_token_program)]
+    pub ask_dst: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// LP Mint, read for the supply
+    #[account(address = pool.load()?.lp_mint @ ErrorCode::UnmatchPool)]
+    pub lp_mint: InterfaceAccount<'info, token_interface::Mint>,
+    /// Token account of the fee receiver in the bid mint
+    #[account(
+      mut,
+      token::mint = bid_mint,
+      token::token_program = bid_token_program,
+      constraint = tax_dst.owner == platform_config.fee_receiver @ ErrorCode::InvalidFeeReceiver
+    )]
+    pub tax_dst: InterfaceAccount<'info, token_interface::TokenAccount>,
+    /// CHECK: The pool escrow
+    #[account(seeds = ["escrow".as_bytes(), &pool.key().to_bytes()], bump)]
+    pub escrow: AccountInfo<'info>,
+    /// System programs
+    /// Token program of the bid mint, SPL Token or Token-2022
+    pub bid_token_program: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of the ask mint, SPL Token or Token-2022
+    pub ask_token_program: Interface<'info, token_interface::TokenInterface>,
+}
+
+impl LeanSwap<'_> {
+    pub fn invoke(ctx: Context<LeanSwap>, bid_amount: u64, limit: u64) -> Result<u64> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+        let platform_config = &ctx.accounts.platform_config;
+        let seeds: &[&[&[u8]]] = &[&[
+            "escrow".as_ref(),
+            &ctx.accounts.pool.key().to_bytes(),
+            &[ctx.bumps.escrow],
+        ]];
+
+        if !pool.is_active() {
+            return err!(ErrorCode::InvalidState);
+        }
+        if platform_config.emergency_paused {
+            return err!(ErrorCode::EmergencyPaused);
+        }
+        if pool.is_paused(PAUSE_SWAP) {
+            return err!(ErrorCode::OperationPaused);
+        }
+        if bid_amount == 0 {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        // Detecting the direction also validates the mints
+        let direction = pool
+            .detect_direction(ctx.accounts.bid_mint.key(), ctx.accounts.ask_mint.key())
+            .ok_or(ErrorCode::UnmatchPool)?;
+
+        let fee = pool.calc_fee(bid_amount).ok_or(ErrorCode::Overflow)?;
+        let tax = platform_config
+            .calc_tax(bid_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        let bid_amount_after_fee_and_tax = bid_amount
+            .checked_sub(fee)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_sub(tax)
+            .ok_or(ErrorCode::Overflow)?;
+        let ask_amount = pool
+            .calc_ask_amount(
+                bid_amount_after_fee_and_tax,
+                ctx.accounts.bid_treasury.amount,
+                ctx.accounts.ask_treasury.amount,
+            )
+            .ok_or(ErrorCode::Overflow)?;
+        if ask_amount < limit {
+            return err!(ErrorCode::LargeSlippage);
+        }
+
+        // Transfer bid tokens
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.bid_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.bid_src.to_account_info(),
+                    mint: ctx.accounts.bid_mint.to_account_info(),
+                    to: ctx.accounts.bid_treasury.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            bid_amount.checked_sub(tax).ok_or(ErrorCode::Overflow)?,
+            ctx.accounts.bid_mint.decimals,
+        )?;
+        // Transfer ask tokens
+        token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                ctx.accounts.ask_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.ask_treasury.to_account_info(),
+                    mint: ctx.accounts.ask_mint.to_account_info(),
+                    to: ctx.accounts.ask_dst.to_account_info(),
+                    authority: ctx.accounts.escrow.to_account_info(),
+                },
+                seeds,
+            ),
+            ask_amount,
+            ctx.accounts.ask_mint.decimals,
+        )?;
+        // Transfer the tax aka. the platform fee
+        token_interface::transfer_checked(
+            CpiContext::new(
+                ctx.accounts.bid_token_program.to_account_info(),
+                token_interface::TransferChecked {
+                    from: ctx.accounts.bid_src.to_account_info(),
+                    mint: ctx.accounts.bid_mint.to_account_info(),
+                    to: ctx.accounts.tax_dst.to_account_info(),
+                    authority: ctx.accounts.authority.to_account_info(),
+                },
+            ),
+            tax,
+            ctx.accounts.bid_mint.decimals,
+        )?;
+
+        pool.accrue_lp_fee(direction, fee)
+            .ok_or(ErrorCode::Overflow)?;
+        let clock = Clock::get()?;
+        ctx.accounts
+            .swap_counter
+            .record_swap(clock.unix_timestamp)
+            .ok_or(ErrorCode::Overflow)?;
+
+        // Reserves after the trade, the treasuries are not reloaded
+        let (reserve_a, reserve_b) = pool
+            .calc_swap_reserves(
+                direction,
+                ctx.accounts
+                    .bid_treasury
+                    .amount
+                    .checked_add(bid_amount)
+                    .ok_or(ErrorCode::Overflow)?
+                    .checked_sub(tax)
+                    .ok_or(ErrorCode::Overflow)?,
+                ctx.accounts
+                    .ask_treasury
+                    .amount
+                    .checked_sub(ask_amount)
+                    .ok_or(ErrorCode::Overflow)?,
+            )
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit_cpi!(SwapEvent {
+            authority: ctx.accounts.authority.key(),
+            pool: ctx.accounts.pool.key(),
+            bid_mint: ctx.accounts.bid_mint.key(),
+            ask_mint: ctx.accounts.ask_mint.key(),
+            bid_amount,
+            ask_amount,
+            lp_fee: fee,
+            tax,
+            referral_fee: 0,
+            referrer: Pubkey::default(),
+            reserve_a,
+            reserve_b,
+            lp_supply: ctx.accounts.lp_mint.supply,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
+        });
+
+        Ok(ask_amount)
+    }
+}
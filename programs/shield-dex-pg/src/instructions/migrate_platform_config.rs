@@ -0,0 +1,73 @@
+use crate::{
+    constants::{ACCOUNT_DISCRIMINATOR, PLATFORM_CONFIG_VERSION},
+    errors::ErrorCode,
+    schema::platform_config::{PlatformConfig, PlatformConfigV0},
+    utils::realloc_account,
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event]
+pub struct MigratePlatformConfigEvent {
+    pub authority: Pubkey,
+    pub platform_config: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigratePlatformConfig<'info> {
+    /// Anyone can migrate, paying the rent of the grown account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: Platform config of an older layout, deserialized once converted
+    #[account(mut, owner = crate::ID)]
+    pub platform_config: AccountInfo<'info>,
+    /// System programs
+    pub system_program: Program<'info, System>,
+}
+
+impl MigratePlatformConfig<'_> {
+    ///
+    /// Baseline configs are converted to the current layout
+    /// The layouts are told apart by their size
+    /// They keep their addresses, so they are left out of the count of the seeded configs
+    ///
+    pub fn invoke(ctx: Context<MigratePlatformConfig>) -> Result<()> {
+        let platform_config = &ctx.accounts.platform_config;
+
+        if platform_config
+            .try_borrow_data()?
+            .get(..ACCOUNT_DISCRIMINATOR)
+            != Some(&PlatformConfig::DISCRIMINATOR[..])
+        {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+        let legacy = match platform_config.data_len() {
+            PlatformConfig::LEN => return err!(ErrorCode::AlreadyMigrated),
+            PlatformConfigV0::LEN => PlatformConfigV0::deserialize(
+                &mut &platform_config.try_borrow_data()?[ACCOUNT_DISCRIMINATOR..],
+            )?,
+            _ => return err!(ErrorCode::UnsupportedLayout),
+        };
+
+        realloc_account(
+            platform_config,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            PlatformConfig::LEN,
+        )?;
+        legacy
+            .migrate()
+            .try_serialize(&mut &mut platform_config.try_borrow_mut_data()?[..])?;
+
+        emit_cpi!(MigratePlatformConfigEvent {
+            authority: ctx.accounts.authority.key(),
+            platform_config: platform_config.key(),
+            from_version: 0,
+            to_version: PLATFORM_CONFIG_VERSION
+        });
+
+        Ok(())
+    }
+}
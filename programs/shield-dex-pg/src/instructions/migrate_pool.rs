@@ -0,0 +1,142 @@
+use crate::{
+    constants::{ACCOUNT_DISCRIMINATOR, POOL_VERSION},
+    errors::ErrorCode,
+    schema::{
+        pool::{Pool, PoolV0},
+        pool_index::PoolIndex,
+        program_state::ProgramState,
+    },
+    utils::{mint_risk_flags, realloc_account},
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event]
+pub struct MigratePoolEvent {
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub id: u64,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(index_page_a: u32, index_page_b: u32)]
+pub struct MigratePool<'info> {
+    /// Anyone can migrate, paying the rent of the grown account
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Program state assigning the pool id
+    #[account(mut, seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Box<Account<'info, ProgramState>>,
+    /// CHECK: Pool of an older layout, loaded once converted
+    #[account(mut, owner = crate::ID)]
+    pub pool: AccountInfo<'info>,
+    /// CHECK: Mint A of the pool, checked against the legacy pool
+    pub mint_a: AccountInfo<'info>,
+    /// CHECK: Mint B of the pool, checked against the legacy pool
+    pub mint_b: AccountInfo<'info>,
+    /// Pool index page of mint A
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = PoolIndex::space(0),
+      seeds = ["pool_index".as_bytes(), &mint_a.key().to_bytes(), &index_page_a.to_le_bytes()],
+      bump
+    )]
+    pub pool_index_a: Box<Account<'info, PoolIndex>>,
+    /// Previous pool index page of mint A, required past the first page
+    #[account(
+      seeds = ["pool_index".as_bytes(), &mint_a.key().to_bytes(), &index_page_a.saturating_sub(1).to_le_bytes()],
+      bump
+    )]
+    pub prev_pool_index_a: Option<Box<Account<'info, PoolIndex>>>,
+    /// Pool index page of mint B
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = PoolIndex::space(0),
+      seeds = ["pool_index".as_bytes(), &mint_b.key().to_bytes(), &index_page_b.to_le_bytes()],
+      bump
+    )]
+    pub pool_index_b: Box<Account<'info, PoolIndex>>,
+    /// Previous pool index page of mint B, required past the first page
+    #[account(
+      seeds = ["pool_index".as_bytes(), &mint_b.key().to_bytes(), &index_page_b.saturating_sub(1).to_le_bytes()],
+      bump
+    )]
+    pub prev_pool_index_b: Option<Box<Account<'info, PoolIndex>>>,
+    /// System programs
+    pub system_program: Program<'info, System>,
+}
+
+impl MigratePool<'_> {
+    ///
+    /// Baseline pools are Borsh-serialized and get converted to the zero-copy layout
+    /// The layouts are told apart by their size
+    /// Baseline pools predate the pool index, so they get listed as well
+    ///
+    pub fn invoke(ctx: Context<MigratePool>, index_page_a: u32, index_page_b: u32) -> Result<()> {
+        let pool = &ctx.accounts.pool;
+
+        if pool.try_borrow_data()?.get(..ACCOUNT_DISCRIMINATOR) != Some(&Pool::DISCRIMINATOR[..]) {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+        let legacy = match pool.data_len() {
+            Pool::LEN => return err!(ErrorCode::AlreadyMigrated),
+            PoolV0::LEN => {
+                PoolV0::deserialize(&mut &pool.try_borrow_data()?[ACCOUNT_DISCRIMINATOR..])?
+            }
+            _ => return err!(ErrorCode::UnsupportedLayout),
+        };
+        if ctx.accounts.mint_a.key() != legacy.mint_a || ctx.accounts.mint_b.key() != legacy.mint_b
+        {
+            return err!(ErrorCode::UnmatchPool);
+        }
+
+        // Baseline pools predate the risk checks and the pool ids
+        let risk_flags =
+            mint_risk_flags(&ctx.accounts.mint_a)? | mint_risk_flags(&ctx.accounts.mint_b)?;
+        let program_state = &mut ctx.accounts.program_state;
+        let id = program_state.total_pools;
+        program_state.total_pools = program_state
+            .total_pools
+            .checked_add(1)
+            .ok_or(ErrorCode::Overflow)?;
+        program_state.updated_at = Clock::get()?.unix_timestamp;
+
+        realloc_account(
+            pool,
+            &ctx.accounts.authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+            Pool::LEN,
+        )?;
+        let mut data = pool.try_borrow_mut_data()?;
+        *bytemuck::from_bytes_mut::<Pool>(&mut data[ACCOUNT_DISCRIMINATOR..Pool::LEN]) =
+            legacy.migrate(id, risk_flags);
+        drop(data);
+
+        PoolIndex::check_page(index_page_a, ctx.accounts.prev_pool_index_a.as_deref())?;
+        PoolIndex::check_page(index_page_b, ctx.accounts.prev_pool_index_b.as_deref())?;
+        let authority = ctx.accounts.authority.to_account_info();
+        let system_program = ctx.accounts.system_program.to_account_info();
+        let pool_index_a = &mut ctx.accounts.pool_index_a;
+        pool_index_a.mint = ctx.accounts.mint_a.key();
+        pool_index_a.page = index_page_a;
+        PoolIndex::push(pool_index_a, pool.key(), &authority, &system_program)?;
+        let pool_index_b = &mut ctx.accounts.pool_index_b;
+        pool_index_b.mint = ctx.accounts.mint_b.key();
+        pool_index_b.page = index_page_b;
+        PoolIndex::push(pool_index_b, pool.key(), &authority, &system_program)?;
+
+        emit_cpi!(MigratePoolEvent {
+            authority: ctx.accounts.authority.key(),
+            pool: pool.key(),
+            id,
+            from_version: 0,
+            to_version: POOL_VERSION
+        });
+
+        Ok(())
+    }
+}
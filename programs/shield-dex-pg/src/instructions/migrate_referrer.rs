@@ -0,0 +1,93 @@
+use crate::{
+    constants::{ACCOUNT_DISCRIMINATOR, REFERRER_VERSION},
+    errors::ErrorCode,
+    schema::referer::{Referrer, ReferrerV0},
+    utils::close_account,
+};
+use anchor_lang::{prelude::*, Discriminator};
+
+#[event]
+pub struct MigrateReferrerEvent {
+    pub authority: Pubkey,
+    pub legacy_referrer: Pubkey,
+    pub referrer: Pubkey,
+    pub owner: Pubkey,
+    pub referee: Pubkey,
+    pub from_version: u8,
+    pub to_version: u8,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct MigrateReferrer<'info> {
+    /// Anyone can migrate, paying the rent of the new binding
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: Referee of the binding, who paid for the legacy one and collects its rent
+    #[account(mut)]
+    pub referee: AccountInfo<'info>,
+    /// CHECK: Baseline binding, seeded by the referee only, closed once moved
+    #[account(
+      mut,
+      owner = crate::ID,
+      seeds = ["referrer".as_bytes(), &referee.key().to_bytes()],
+      bump
+    )]
+    pub legacy_referrer: AccountInfo<'info>,
+    /// Global binding of the referee, kept when the referee has already bound again
+    #[account(
+      init_if_needed,
+      payer = authority,
+      seeds = ["referrer".as_bytes(), &Pubkey::default().to_bytes(), &referee.key().to_bytes()],
+      space = Referrer::LEN,
+      bump
+    )]
+    pub referrer: Account<'info, Referrer>,
+    /// System programs
+    pub system_program: Program<'info, System>,
+}
+
+impl MigrateReferrer<'_> {
+    ///
+    /// Baseline bindings never set their pool, so they move to the global scope
+    ///
+    pub fn invoke(ctx: Context<MigrateReferrer>) -> Result<()> {
+        let legacy_referrer = &ctx.accounts.legacy_referrer;
+
+        if legacy_referrer
+            .try_borrow_data()?
+            .get(..ACCOUNT_DISCRIMINATOR)
+            != Some(&Referrer::DISCRIMINATOR[..])
+        {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+        if legacy_referrer.data_len() != ReferrerV0::LEN {
+            return err!(ErrorCode::UnsupportedLayout);
+        }
+        let legacy = ReferrerV0::deserialize(
+            &mut &legacy_referrer.try_borrow_data()?[ACCOUNT_DISCRIMINATOR..],
+        )?;
+        if legacy.referee != ctx.accounts.referee.key() {
+            return err!(ErrorCode::InvalidReferer);
+        }
+
+        // A referee who has already bound again keeps the newer binding
+        let referrer = &mut ctx.accounts.referrer;
+        if referrer.referee == Pubkey::default() {
+            referrer.set_inner(legacy.migrate());
+        }
+        close_account(legacy_referrer, &ctx.accounts.referee)?;
+
+        emit_cpi!(MigrateReferrerEvent {
+            authority: ctx.accounts.authority.key(),
+            legacy_referrer: legacy_referrer.key(),
+            referrer: referrer.key(),
+            owner: referrer.owner,
+            referee: referrer.referee,
+            from_version: 0,
+            to_version: REFERRER_VERSION
+        });
+
+        Ok(())
+    }
+}
@@ -1,14 +1,32 @@
 pub mod add_liquidity;
+pub mod cancel_change;
+pub mod cancel_pool;
+pub mod claim_referral_rewards;
+pub mod close_pool;
+pub mod close_referrer;
 pub mod create_referrer;
+pub mod create_referrer_by_code;
+pub mod execute_change;
+pub mod grant_role;
 pub mod initialize;
+pub mod lean_swap;
+pub mod migrate_platform_config;
+pub mod migrate_pool;
+pub mod migrate_referrer;
 pub mod pause;
+pub mod register_code;
 pub mod remove_liquidity;
 pub mod resume;
+pub mod revoke_role;
 
 pub mod swap;
 pub mod transfer_ownership;
+pub mod unbind_referrer;
 pub mod update_lp_fee;
+pub mod update_lp_uri;
+pub mod update_pool_guardian;
 pub mod update_referral_fee;
+pub mod update_referrer;
 pub mod update_tax;
 
 pub mod admin;
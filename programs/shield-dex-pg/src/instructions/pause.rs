@@ -1,37 +1,58 @@
+use crate::constants::{PAUSE_ALL, ROLE_PAUSER};
 use crate::errors::ErrorCode;
-use crate::schema::pool::*;
+use crate::schema::{access_control::AccessControl, platform_config::PlatformConfig, pool::*};
 use anchor_lang::prelude::*;
 
 #[event]
 pub struct PauseEvent {
     pub authority: Pubkey,
     pub pool: Pubkey,
+    pub pause_flags: u8,
     pub updated_at: i64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Pause<'info> {
-    /// Authority
+    /// Owner, pauser or guardian
+    #[account(
+      constraint = (pool.load()?.can_pause(authority.key(), platform_config.guardian)
+        || pool.load()?.is_granted(access_control.as_deref(), authority.key(), ROLE_PAUSER)) @ ErrorCode::Unauthorized
+    )]
     pub authority: Signer<'info>,
     /// Pool
-    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
-    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Access control
+    #[account(
+      has_one = pool @ ErrorCode::UnmatchPool,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Option<Account<'info, AccessControl>>,
+    /// Which config the pool belongs to.
+    #[account(address = pool.load()?.tax @ ErrorCode::InvalidPlatformConfig)]
+    pub platform_config: Account<'info, PlatformConfig>,
 }
 
 impl Pause<'_> {
-    pub fn invoke(ctx: Context<Pause>) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
+    pub fn invoke(ctx: Context<Pause>, flags: u8) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
 
-        if pool.state != PoolState::Initialized {
+        if pool.state() != PoolState::Initialized {
             return err!(ErrorCode::InvalidState);
         }
+        if flags == 0 || flags & !PAUSE_ALL != 0 {
+            return err!(ErrorCode::InvalidParams);
+        }
 
-        pool.state = PoolState::Paused;
+        pool.pause_flags |= flags;
         pool.updated_at = Clock::get()?.unix_timestamp;
 
-        emit!(PauseEvent {
+        emit_cpi!(PauseEvent {
             authority: ctx.accounts.authority.key(),
-            pool: pool.key(),
+            pool: ctx.accounts.pool.key(),
+            pause_flags: pool.pause_flags,
             updated_at: pool.updated_at
         });
 
@@ -0,0 +1,48 @@
+use crate::schema::referral_code::ReferralCode;
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct RegisterCodeEvent {
+    pub owner: Pubkey,
+    pub referral_code: Pubkey,
+    pub code: String,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(code: String)]
+pub struct RegisterCode<'info> {
+    /// Referrer owning the code
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    /// Referral code, failing if the code is taken
+    #[account(
+      init,
+      payer = owner,
+      space = ReferralCode::LEN,
+      seeds = ["referral_code".as_bytes(), ReferralCode::seed(&code)?],
+      bump
+    )]
+    pub referral_code: Account<'info, ReferralCode>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl RegisterCode<'_> {
+    pub fn invoke(ctx: Context<RegisterCode>, code: String) -> Result<()> {
+        let referral_code = &mut ctx.accounts.referral_code;
+
+        referral_code.owner = ctx.accounts.owner.key();
+        referral_code.code = code;
+        referral_code.bump = ctx.bumps.referral_code;
+        referral_code.created_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(RegisterCodeEvent {
+            owner: referral_code.owner,
+            referral_code: referral_code.key(),
+            code: referral_code.code.clone(),
+        });
+
+        Ok(())
+    }
+}
@@ -1,6 +1,10 @@
-use crate::{errors::ErrorCode, schema::pool::Pool};
+use crate::{
+    constants::PAUSE_WITHDRAW,
+    errors::ErrorCode,
+    schema::{platform_config::PlatformConfig, pool::Pool},
+};
 use anchor_lang::prelude::*;
-use anchor_spl::{associated_token, token};
+use anchor_spl::{associated_token, token_interface};
 use num::ToPrimitive;
 
 #[event]
@@ -10,8 +14,14 @@ pub struct RemoveLiquidityEvent {
     pub a: u64,
     pub b: u64,
     pub lp: u64,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub lp_supply: u64,
+    pub timestamp: i64,
+    pub slot: u64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct RemoveLiquidity<'info> {
     /// Authority
@@ -23,52 +33,67 @@ pub struct RemoveLiquidity<'info> {
     has_one = mint_b @ ErrorCode::UnmatchPool,
     has_one = lp_mint @ ErrorCode::UnmatchPool
   )]
-    pub pool: Account<'info, Pool>,
+    pub pool: AccountLoader<'info, Pool>,
+    /// Which config the pool belongs to.
+    #[account(address = pool.load()?.tax @ ErrorCode::InvalidPlatformConfig)]
+    pub platform_config: Box<Account<'info, PlatformConfig>>,
     /// Mint A
-    pub mint_a: Box<Account<'info, token::Mint>>,
+    #[account(mint::token_program = token_program_a)]
+    pub mint_a: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
     mut,
     associated_token::mint = mint_a,
-    associated_token::authority = escrow
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program_a
   )]
-    pub treasury_a: Box<Account<'info, token::TokenAccount>>,
+    pub treasury_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
     init_if_needed,
     payer = authority,
     associated_token::mint = mint_a,
-    associated_token::authority = authority
+    associated_token::authority = authority,
+    associated_token::token_program = token_program_a
   )]
-    pub dst_a: Box<Account<'info, token::TokenAccount>>,
+    pub dst_a: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     /// Mint B
-    pub mint_b: Box<Account<'info, token::Mint>>,
+    #[account(mint::token_program = token_program_b)]
+    pub mint_b: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
     mut,
     associated_token::mint = mint_b,
-    associated_token::authority = escrow
+    associated_token::authority = escrow,
+    associated_token::token_program = token_program_b
   )]
-    pub treasury_b: Box<Account<'info, token::TokenAccount>>,
+    pub treasury_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
     init_if_needed,
     payer = authority,
     associated_token::mint = mint_b,
-    associated_token::authority = authority
+    associated_token::authority = authority,
+    associated_token::token_program = token_program_b
   )]
-    pub dst_b: Box<Account<'info, token::TokenAccount>>,
+    pub dst_b: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     // LP Mint
-    #[account(mut)]
-    pub lp_mint: Box<Account<'info, token::Mint>>,
+    #[account(mut, mint::token_program = lp_token_program)]
+    pub lp_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
     init_if_needed,
     payer = authority,
     associated_token::mint = lp_mint,
-    associated_token::authority = authority
+    associated_token::authority = authority,
+    associated_token::token_program = lp_token_program
   )]
-    pub src_lp: Box<Account<'info, token::TokenAccount>>,
+    pub src_lp: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     /// CHECK: The pool escrow
     #[account(seeds = ["escrow".as_bytes(), &pool.key().to_bytes()], bump)]
     pub escrow: AccountInfo<'info>,
     /// System programs
-    pub token_program: Program<'info, token::Token>,
+    /// Token program of mint A, SPL Token or Token-2022
+    pub token_program_a: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of mint B, SPL Token or Token-2022
+    pub token_program_b: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of the LP mint, Token-2022 for pools created with metadata
+    pub lp_token_program: Interface<'info, token_interface::TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
@@ -76,22 +101,28 @@ pub struct RemoveLiquidity<'info> {
 
 impl RemoveLiquidity<'_> {
     pub fn invoke(ctx: Context<RemoveLiquidity>, lp: u64) -> Result<()> {
-        let pool = &ctx.accounts.pool;
+        let pool = ctx.accounts.pool.load()?;
         let seeds: &[&[&[u8]]] = &[&[
             "escrow".as_ref(),
-            &pool.key().to_bytes(),
+            &ctx.accounts.pool.key().to_bytes(),
             &[ctx.bumps.escrow],
         ]];
 
-        if lp <= 0 {
+        if ctx.accounts.platform_config.emergency_paused {
+            return err!(ErrorCode::EmergencyPaused);
+        }
+        if pool.is_paused(PAUSE_WITHDRAW) {
+            return err!(ErrorCode::OperationPaused);
+        }
+        if lp == 0 {
             return err!(ErrorCode::InvalidParams);
         }
 
         // Burn LP tokens
-        token::burn(
+        token_interface::burn(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Burn {
+                ctx.accounts.lp_token_program.to_account_info(),
+                token_interface::Burn {
                     mint: ctx.accounts.lp_mint.to_account_info(),
                     from: ctx.accounts.src_lp.to_account_info(),
                     authority: ctx.accounts.authority.to_account_info(),
@@ -118,39 +149,57 @@ impl RemoveLiquidity<'_> {
 
         // Withdraw token A | a = lp * reserve_a / liquidity
         let a = Pool::hydrate_liquidity(lp, reserve_a, liquidity).ok_or(ErrorCode::Overflow)?;
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.token_program_a.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.treasury_a.to_account_info(),
+                    mint: ctx.accounts.mint_a.to_account_info(),
                     to: ctx.accounts.dst_a.to_account_info(),
                     authority: ctx.accounts.escrow.to_account_info(),
                 },
                 seeds,
             ),
             a,
+            ctx.accounts.mint_a.decimals,
         )?;
         // Withdraw token B | b = lp * reserve_b / liquidity
         let b = Pool::hydrate_liquidity(lp, reserve_b, liquidity).ok_or(ErrorCode::Overflow)?;
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.token_program_b.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.treasury_b.to_account_info(),
+                    mint: ctx.accounts.mint_b.to_account_info(),
                     to: ctx.accounts.dst_b.to_account_info(),
                     authority: ctx.accounts.escrow.to_account_info(),
                 },
                 seeds,
             ),
             b,
+            ctx.accounts.mint_b.decimals,
         )?;
 
-        emit!(RemoveLiquidityEvent {
+        // Reserves and supply after the withdrawal, the accounts are not reloaded
+        let clock = Clock::get()?;
+
+        emit_cpi!(RemoveLiquidityEvent {
             authority: ctx.accounts.authority.key(),
-            pool: pool.key(),
+            pool: ctx.accounts.pool.key(),
             a,
             b,
-            lp
+            lp,
+            reserve_a: reserve_a
+                .checked_sub(a.into())
+                .and_then(|reserve| reserve.to_u64())
+                .ok_or(ErrorCode::Overflow)?,
+            reserve_b: reserve_b
+                .checked_sub(b.into())
+                .and_then(|reserve| reserve.to_u64())
+                .ok_or(ErrorCode::Overflow)?,
+            lp_supply: liquidity.checked_sub(lp).ok_or(ErrorCode::Overflow)?,
+            timestamp: clock.unix_timestamp,
+            slot: clock.slot,
         });
 
         Ok(())
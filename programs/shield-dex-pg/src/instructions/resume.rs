@@ -1,37 +1,52 @@
+use crate::constants::{PAUSE_ALL, ROLE_PAUSER};
 use crate::errors::ErrorCode;
-use crate::schema::pool::*;
+use crate::schema::{access_control::AccessControl, pool::*};
 use anchor_lang::prelude::*;
 
 #[event]
 pub struct ResumeEvent {
     pub authority: Pubkey,
     pub pool: Pubkey,
+    pub pause_flags: u8,
     pub updated_at: i64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Resume<'info> {
-    /// Authority
+    /// Owner or pauser
+    #[account(constraint = pool.load()?.is_granted(access_control.as_deref(), authority.key(), ROLE_PAUSER) @ ErrorCode::Unauthorized)]
     pub authority: Signer<'info>,
     /// Pool
-    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
-    pub pool: Account<'info, Pool>,
+    #[account(mut)]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Access control
+    #[account(
+      has_one = pool @ ErrorCode::UnmatchPool,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Option<Account<'info, AccessControl>>,
 }
 
 impl Resume<'_> {
-    pub fn invoke(ctx: Context<Resume>) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
+    pub fn invoke(ctx: Context<Resume>, flags: u8) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
 
-        if pool.state != PoolState::Paused {
-            return err!(ErrorCode::InvalidState);
+        if flags == 0 || flags & !PAUSE_ALL != 0 {
+            return err!(ErrorCode::InvalidParams);
         }
 
-        pool.state = PoolState::Initialized;
+        if pool.state() != PoolState::Initialized {
+            return err!(ErrorCode::InvalidState);
+        }
+        pool.pause_flags &= !flags;
         pool.updated_at = Clock::get()?.unix_timestamp;
 
-        emit!(ResumeEvent {
+        emit_cpi!(ResumeEvent {
             authority: ctx.accounts.authority.key(),
-            pool: pool.key(),
+            pool: ctx.accounts.pool.key(),
+            pause_flags: pool.pause_flags,
             updated_at: pool.updated_at
         });
 
@@ -0,0 +1,53 @@
+use crate::constants::ROLE_ALL;
+use crate::errors::ErrorCode;
+use crate::schema::{access_control::AccessControl, pool::*};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct RevokeRoleEvent {
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub grantee: Pubkey,
+    pub roles: u8,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    /// Authority
+    pub authority: Signer<'info>,
+    /// Pool
+    #[account(has_one = authority @ ErrorCode::Unauthorized)]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Access control
+    #[account(
+      mut,
+      has_one = pool @ ErrorCode::UnmatchPool,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Account<'info, AccessControl>,
+}
+
+impl RevokeRole<'_> {
+    pub fn invoke(ctx: Context<RevokeRole>, grantee: Pubkey, roles: u8) -> Result<()> {
+        let access_control = &mut ctx.accounts.access_control;
+
+        if roles == 0 || roles & !ROLE_ALL != 0 {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        let roles = access_control.revoke(grantee, roles);
+
+        emit_cpi!(RevokeRoleEvent {
+            authority: ctx.accounts.authority.key(),
+            pool: ctx.accounts.pool.key(),
+            grantee,
+            roles,
+            updated_at: Clock::get()?.unix_timestamp
+        });
+
+        Ok(())
+    }
+}
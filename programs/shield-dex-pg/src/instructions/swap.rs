@@ -1,13 +1,15 @@
 use crate::{
+    constants::{MAX_REFERRAL_TIERS, PAUSE_SWAP},
     errors::ErrorCode,
-    schema::{platform_config::PlatformConfig, pool::Pool},
+    schema::{
+        platform_config::PlatformConfig, pool::Pool, referer::Referrer,
+        referral_rewards::ReferralRewards, referral_stats::ReferralStats,
+        swap_counter::SwapCounter,
+    },
+    utils::{create_pda_account, realloc_account},
 };
 use anchor_lang::prelude::*;
-use anchor_spl::{
-    associated_token,
-    token::{self},
-};
-use num::ToPrimitive;
+use anchor_spl::{associated_token, token_interface};
 
 #[event]
 pub struct SwapEvent {
@@ -17,54 +19,93 @@ pub struct SwapEvent {
     pub ask_mint: Pubkey,
     pub bid_amount: u64,
     pub ask_amount: u64,
+    pub lp_fee: u64,
+    pub tax: u64,
+    pub referral_fee: u64,
+    pub referrer: Pubkey,
+    pub reserve_a: u64,
+    pub reserve_b: u64,
+    pub lp_supply: u64,
+    pub timestamp: i64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AccrueReferralRewardsEvent {
+    pub owner: Pubkey,
+    pub referee: Pubkey,
+    pub pool: Pubkey,
+    pub mint: Pubkey,
+    pub tier: u8,
+    pub amount: u64,
+    pub accrued: u64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct Swap<'info> {
     /// Authority
     #[account(mut)]
     pub authority: Signer<'info>,
     // AMM config
-    #[account(address = pool.tax)]
+    #[account(address = pool.load()?.tax)]
     pub platform_config: Box<Account<'info, PlatformConfig>>,
     /// Pool
     #[account(mut)]
-    pub pool: Account<'info, Pool>,
-    /// CHECK: The pool fee reveiver
+    pub pool: AccountLoader<'info, Pool>,
+    /// Shard of the swap counter the pool counts its swaps in
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = SwapCounter::LEN,
+      seeds = ["swap_counter".as_bytes(), &[pool.load()?.swap_counter_shard()]],
+      bump
+    )]
+    pub swap_counter: Box<Account<'info, SwapCounter>>,
+    /// CHECK: The fee receiver of the platform config
     #[account(
         mut,
-        address= crate::create_pool_fee_reveiver::id(),
+        address = platform_config.fee_receiver @ ErrorCode::InvalidFeeReceiver,
     )]
     pub taxman: AccountInfo<'info>,
     /// Bid Mint
-    pub bid_mint: Box<Account<'info, token::Mint>>,
+    #[account(mint::token_program = bid_token_program)]
+    pub bid_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
     mut,
     associated_token::mint = bid_mint,
-    associated_token::authority = authority
+    associated_token::authority = authority,
+    associated_token::token_program = bid_token_program
   )]
-    pub bid_src: Box<Account<'info, token::TokenAccount>>,
+    pub bid_src: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
     mut,
     associated_token::mint = bid_mint,
-    associated_token::authority = escrow
+    associated_token::authority = escrow,
+    associated_token::token_program = bid_token_program
   )]
-    pub bid_treasury: Box<Account<'info, token::TokenAccount>>,
+    pub bid_treasury: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     /// Ask Mint
-    pub ask_mint: Box<Account<'info, token::Mint>>,
+    #[account(mint::token_program = ask_token_program)]
+    pub ask_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
     #[account(
     mut,
     associated_token::mint = ask_mint,
-    associated_token::authority = escrow
+    associated_token::authority = escrow,
+    associated_token::token_program = ask_token_program
   )]
-    pub ask_treasury: Box<Account<'info, token::TokenAccount>>,
+    pub ask_treasury: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
     #[account(
     init_if_needed,
     payer = authority,
     associated_token::mint = ask_mint,
-    associated_token::authority = authority
+    associated_token::authority = authority,
+    associated_token::token_program = ask_token_program
   )]
-    pub ask_dst: Box<Account<'info, token::TokenAccount>>,
+    pub ask_dst: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// LP Mint, read for the supply
+    #[account(address = pool.load()?.lp_mint @ ErrorCode::UnmatchPool)]
+    pub lp_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
     /// CHECK: The pool escrow
     #[account(seeds = ["escrow".as_bytes(), &pool.key().to_bytes()], bump)]
     pub escrow: AccountInfo<'info>,
@@ -73,35 +114,95 @@ pub struct Swap<'info> {
     payer = authority,
     associated_token::mint = bid_mint,
     associated_token::authority = taxman,
-   
+    associated_token::token_program = bid_token_program
   )]
-    pub tax_dst: Box<Account<'info, token::TokenAccount>>,
+    pub tax_dst: Box<InterfaceAccount<'info, token_interface::TokenAccount>>,
+    /// Referrer of the authority, bound to the pool or global
+    #[account(
+      constraint = referrer.referee == authority.key() @ ErrorCode::InvalidReferer,
+      constraint = referrer.is_bound() @ ErrorCode::InvalidReferer,
+      constraint = (referrer.pool == pool.key() || referrer.is_global()) @ ErrorCode::InvalidReferer
+    )]
+    pub referrer: Option<Box<Account<'info, Referrer>>>,
+    /// Balance of the referrer in the bid mint
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = ReferralRewards::LEN,
+      seeds = [
+        "referral_rewards".as_bytes(),
+        &referrer.as_ref().map(|referrer| referrer.owner).unwrap_or_default().to_bytes(),
+        &bid_mint.key().to_bytes()
+      ],
+      bump
+    )]
+    pub referral_rewards: Option<Box<Account<'info, ReferralRewards>>>,
+    /// CHECK: The referral vault holding the unclaimed rewards
+    #[account(seeds = ["referral_vault".as_bytes()], bump)]
+    pub referral_vault: Option<AccountInfo<'info>>,
+    #[account(
+      init_if_needed,
+      payer = authority,
+      associated_token::mint = bid_mint,
+      associated_token::authority = referral_vault,
+      associated_token::token_program = bid_token_program
+    )]
+    pub referral_treasury: Option<Box<InterfaceAccount<'info, token_interface::TokenAccount>>>,
+    /// CHECK: Stats of the referrer, verified and created on the first swap
+    #[account(mut)]
+    pub referral_stats: Option<AccountInfo<'info>>,
     /// System programs
-    pub token_program: Program<'info, token::Token>,
+    /// Token program of the bid mint, SPL Token or Token-2022
+    pub bid_token_program: Interface<'info, token_interface::TokenInterface>,
+    /// Token program of the ask mint, SPL Token or Token-2022
+    pub ask_token_program: Interface<'info, token_interface::TokenInterface>,
     pub associated_token_program: Program<'info, associated_token::AssociatedToken>,
     pub system_program: Program<'info, System>,
     pub rent: Sysvar<'info, Rent>,
 }
 
-impl Swap<'_> {
-    pub fn invoke(ctx: Context<Swap>, bid_amount: u64, limit: u64) -> Result<u64> {
-        let pool = &mut ctx.accounts.pool;
+impl<'info> Swap<'info> {
+    ///
+    /// With a referrer, the remaining accounts are the binding, the referral rewards
+    /// in the bid mint and the referral stats of each of its uplines, from the closest
+    ///
+    pub fn invoke(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        bid_amount: u64,
+        limit: u64,
+    ) -> Result<u64> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
         let platform_config: &Account<'_, PlatformConfig> = &ctx.accounts.platform_config;
         let seeds: &[&[&[u8]]] = &[&[
             "escrow".as_ref(),
-            &pool.key().to_bytes(),
+            &ctx.accounts.pool.key().to_bytes(),
             &[ctx.bumps.escrow],
         ]];
 
         if !pool.is_active() {
             return err!(ErrorCode::InvalidState);
         }
-        if bid_amount <= 0 {
+        if platform_config.emergency_paused {
+            return err!(ErrorCode::EmergencyPaused);
+        }
+        if pool.is_paused(PAUSE_SWAP) {
+            return err!(ErrorCode::OperationPaused);
+        }
+        if bid_amount == 0 {
             return err!(ErrorCode::InvalidParams);
         }
+        if ctx.accounts.referrer.is_some()
+            && (ctx.accounts.referral_rewards.is_none()
+                || ctx.accounts.referral_treasury.is_none()
+                || ctx.accounts.referral_stats.is_none())
+        {
+            return err!(ErrorCode::MissingReferralAccount);
+        }
+        let clock = Clock::get()?;
+        let now = clock.unix_timestamp;
 
-        // We ignore the value but MUST keep the function call for mints validation
-        let _direction = pool
+        // Detecting the direction also validates the mints
+        let direction = pool
             .detect_direction(ctx.accounts.bid_mint.key(), ctx.accounts.ask_mint.key())
             .ok_or(ErrorCode::UnmatchPool)?;
 
@@ -115,108 +216,372 @@ impl Swap<'_> {
             .ok_or(ErrorCode::Overflow)?
             .checked_sub(tax)
             .ok_or(ErrorCode::Overflow)?;
-        // Current pool reserves
-        let (bid_reserve, ask_reserve) = pool.vault_amount_without_fee(
-            ctx.accounts
-                .bid_treasury
-                .amount
-                .to_u128()
-                .ok_or(ErrorCode::Overflow)?,
-            ctx.accounts
-                .ask_treasury
-                .amount
-                .to_u128()
-                .ok_or(ErrorCode::Overflow)?,
-        );
-        // Current pool liquidity aka. the product constant
-        let liquidity = bid_reserve
-            .checked_mul(ask_reserve)
-            .ok_or(ErrorCode::Overflow)?;
-        // Next pool reserves
-        let next_bid_reserve = bid_amount_after_fee_and_tax
-            .to_u128()
-            .ok_or(ErrorCode::Overflow)?
-            .checked_add(bid_reserve)
-            .ok_or(ErrorCode::Overflow)?;
-        let next_ask_reserve = liquidity
-            .checked_div(next_bid_reserve)
-            .ok_or(ErrorCode::Overflow)?;
-        // Ask amount, fee amount, tax amount
-        let ask_amount = ask_reserve
-            .checked_sub(next_ask_reserve)
-            .ok_or(ErrorCode::Overflow)?
-            .to_u64()
+        let ask_amount = pool
+            .calc_ask_amount(
+                bid_amount_after_fee_and_tax,
+                ctx.accounts.bid_treasury.amount,
+                ctx.accounts.ask_treasury.amount,
+            )
             .ok_or(ErrorCode::Overflow)?;
 
         if ask_amount < limit {
             return err!(ErrorCode::LargeSlippage);
         }
-        let last_bid_amount = bid_amount.checked_sub(tax).ok_or(ErrorCode::Overflow)?;
+        // A bound referrer takes its share out of the lp fee
+        let referral_amount = match ctx.accounts.referrer {
+            Some(_) => pool.calc_referral_amount(fee).ok_or(ErrorCode::Overflow)?,
+            None => 0,
+        };
+        let last_bid_amount = bid_amount
+            .checked_sub(tax)
+            .ok_or(ErrorCode::Overflow)?
+            .checked_sub(referral_amount)
+            .ok_or(ErrorCode::Overflow)?;
 
         // Transfer bid tokens
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.bid_token_program.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.bid_src.to_account_info(),
+                    mint: ctx.accounts.bid_mint.to_account_info(),
                     to: ctx.accounts.bid_treasury.to_account_info(),
                     authority: ctx.accounts.authority.to_account_info(),
                 },
             ),
             last_bid_amount,
+            ctx.accounts.bid_mint.decimals,
         )?;
         // Transfer ask tokens
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new_with_signer(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.ask_token_program.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.ask_treasury.to_account_info(),
+                    mint: ctx.accounts.ask_mint.to_account_info(),
                     to: ctx.accounts.ask_dst.to_account_info(),
                     authority: ctx.accounts.escrow.to_account_info(),
                 },
                 seeds,
             ),
             ask_amount,
+            ctx.accounts.ask_mint.decimals,
         )?;
-        msg!("ask_amount:sww {}, bid_amount: {}, tax: {}, fee: {}", ask_amount, bid_amount, tax, fee);
         // Transfer the tax aka. the platform fee
-
-        token::transfer(
+        token_interface::transfer_checked(
             CpiContext::new(
-                ctx.accounts.token_program.to_account_info(),
-                token::Transfer {
+                ctx.accounts.bid_token_program.to_account_info(),
+                token_interface::TransferChecked {
                     from: ctx.accounts.bid_src.to_account_info(),
+                    mint: ctx.accounts.bid_mint.to_account_info(),
                     to: ctx.accounts.tax_dst.to_account_info(),
                     authority: ctx.accounts.authority.to_account_info(),
                 },
             ),
             tax,
+            ctx.accounts.bid_mint.decimals,
         )?;
 
-        match _direction {
-            true => {
-                pool.lp_fees_mint_a = pool
-                    .lp_fees_mint_a
-                    .checked_add(fee)
-                    .ok_or(ErrorCode::Overflow)?;
+        // Pay the referral share into the vault, net of any transfer fee of the bid mint
+        let referral_received = match (&ctx.accounts.referrer, &mut ctx.accounts.referral_treasury)
+        {
+            (Some(_), Some(referral_treasury)) => {
+                let prev_balance = referral_treasury.amount;
+                token_interface::transfer_checked(
+                    CpiContext::new(
+                        ctx.accounts.bid_token_program.to_account_info(),
+                        token_interface::TransferChecked {
+                            from: ctx.accounts.bid_src.to_account_info(),
+                            mint: ctx.accounts.bid_mint.to_account_info(),
+                            to: referral_treasury.to_account_info(),
+                            authority: ctx.accounts.authority.to_account_info(),
+                        },
+                    ),
+                    referral_amount,
+                    ctx.accounts.bid_mint.decimals,
+                )?;
+                referral_treasury.reload()?;
+                referral_treasury
+                    .amount
+                    .checked_sub(prev_balance)
+                    .ok_or(ErrorCode::Overflow)?
+            }
+            _ => 0,
+        };
+
+        // Credit what the vault received, paid out of the vault on claim
+        // The uplines take their tier shares, the direct referrer keeps the rest
+        let upline_events = match ctx.accounts.referrer {
+            Some(_) => {
+                ctx.accounts
+                    .credit_uplines(ctx.remaining_accounts, referral_received, now)?
             }
-            false => {
-                pool.lp_fees_mint_b = pool
-                    .lp_fees_mint_b
-                    .checked_add(fee)
-                    .ok_or(ErrorCode::Overflow)?;
+            None => vec![],
+        };
+        let mut direct_amount = referral_received;
+        for event in upline_events {
+            direct_amount = direct_amount
+                .checked_sub(event.amount)
+                .ok_or(ErrorCode::Overflow)?;
+            emit_cpi!(event);
+        }
+        if let (Some(referrer), Some(referral_rewards)) =
+            (&ctx.accounts.referrer, &mut ctx.accounts.referral_rewards)
+        {
+            if !referral_rewards.is_initialized() {
+                referral_rewards.owner = referrer.owner;
+                referral_rewards.mint = ctx.accounts.bid_mint.key();
+                referral_rewards.bump = ctx.bumps.referral_rewards;
+                referral_rewards.created_at = now;
             }
+            referral_rewards
+                .accrue(direct_amount, now)
+                .ok_or(ErrorCode::Overflow)?;
+
+            emit_cpi!(AccrueReferralRewardsEvent {
+                owner: referrer.owner,
+                referee: ctx.accounts.authority.key(),
+                pool: ctx.accounts.pool.key(),
+                mint: ctx.accounts.bid_mint.key(),
+                tier: 0,
+                amount: direct_amount,
+                accrued: referral_rewards.accrued,
+            });
+        }
+
+        if let (Some(referrer), Some(referral_stats)) =
+            (&ctx.accounts.referrer, &ctx.accounts.referral_stats)
+        {
+            ctx.accounts.record_stats(
+                referral_stats,
+                referrer.owner,
+                bid_amount,
+                direct_amount,
+                now,
+            )?;
         }
 
-        emit!(SwapEvent {
+        let lp_fee = fee
+            .checked_sub(referral_amount)
+            .ok_or(ErrorCode::Overflow)?;
+        pool.accrue_lp_fee(direction, lp_fee)
+            .ok_or(ErrorCode::Overflow)?;
+
+        let swap_counter = &mut ctx.accounts.swap_counter;
+        swap_counter.shard = pool.swap_counter_shard();
+        swap_counter.bump = ctx.bumps.swap_counter;
+        swap_counter.record_swap(now).ok_or(ErrorCode::Overflow)?;
+
+        // Reserves after the trade, the treasuries are not reloaded
+        let (reserve_a, reserve_b) = pool
+            .calc_swap_reserves(
+                direction,
+                ctx.accounts
+                    .bid_treasury
+                    .amount
+                    .checked_add(last_bid_amount)
+                    .ok_or(ErrorCode::Overflow)?,
+                ctx.accounts
+                    .ask_treasury
+                    .amount
+                    .checked_sub(ask_amount)
+                    .ok_or(ErrorCode::Overflow)?,
+            )
+            .ok_or(ErrorCode::Overflow)?;
+
+        emit_cpi!(SwapEvent {
             authority: ctx.accounts.authority.key(),
-            pool: pool.key(),
+            pool: ctx.accounts.pool.key(),
             bid_mint: ctx.accounts.bid_mint.key(),
             ask_mint: ctx.accounts.ask_mint.key(),
             bid_amount,
-            ask_amount
+            ask_amount,
+            lp_fee,
+            tax,
+            referral_fee: referral_amount,
+            referrer: ctx
+                .accounts
+                .referrer
+                .as_ref()
+                .map(|referrer| referrer.owner)
+                .unwrap_or_default(),
+            reserve_a,
+            reserve_b,
+            lp_supply: ctx.accounts.lp_mint.supply,
+            timestamp: now,
+            slot: clock.slot,
         });
 
         Ok(ask_amount)
     }
+    ///
+    /// Credit the tier shares of the uplines of the referrer
+    /// Return the events of the credits, emitted by the instruction
+    ///
+    fn credit_uplines(
+        &self,
+        uplines: &[AccountInfo<'info>],
+        referral_amount: u64,
+        now: i64,
+    ) -> Result<Vec<AccrueReferralRewardsEvent>> {
+        let referrer = self
+            .referrer
+            .as_ref()
+            .ok_or(ErrorCode::MissingReferralAccount)?;
+        let mut uplines = uplines.chunks(3);
+        let mut parent = referrer.parent;
+        let mut events = vec![];
+
+        for tier in 1..MAX_REFERRAL_TIERS {
+            if parent == Pubkey::default() {
+                break;
+            }
+            let [upline, rewards, stats] =
+                uplines.next().ok_or(ErrorCode::MissingReferralAccount)?
+            else {
+                return err!(ErrorCode::MissingReferralAccount);
+            };
+            let Some(upline) = Referrer::load_upline(upline, parent)? else {
+                break;
+            };
+            if !upline.is_bound() {
+                break;
+            }
+            let amount = self
+                .platform_config
+                .calc_referral_tier_share(referral_amount, tier)
+                .ok_or(ErrorCode::Overflow)?;
+            let accrued = self.credit_rewards(rewards, upline.owner, amount, now)?;
+            self.record_stats(stats, upline.owner, 0, amount, now)?;
+
+            events.push(AccrueReferralRewardsEvent {
+                owner: upline.owner,
+                referee: self.authority.key(),
+                pool: self.pool.key(),
+                mint: self.bid_mint.key(),
+                tier: tier as u8,
+                amount,
+                accrued,
+            });
+
+            parent = upline.parent;
+        }
+
+        Ok(events)
+    }
+
+    ///
+    /// Credit the referral rewards of an owner in the bid mint, created on the first credit
+    /// Return the accrued balance
+    ///
+    fn credit_rewards(
+        &self,
+        rewards: &AccountInfo<'info>,
+        owner: Pubkey,
+        amount: u64,
+        now: i64,
+    ) -> Result<u64> {
+        let mint = self.bid_mint.key();
+        let (address, bump) = Pubkey::find_program_address(
+            &[
+                "referral_rewards".as_bytes(),
+                &owner.to_bytes(),
+                &mint.to_bytes(),
+            ],
+            &crate::ID,
+        );
+        if rewards.key() != address {
+            return err!(anchor_lang::error::ErrorCode::ConstraintSeeds);
+        }
+
+        let mut state = if rewards.data_is_empty() {
+            create_pda_account(
+                rewards,
+                &self.authority.to_account_info(),
+                &self.system_program.to_account_info(),
+                ReferralRewards::LEN,
+                &[
+                    "referral_rewards".as_bytes(),
+                    &owner.to_bytes(),
+                    &mint.to_bytes(),
+                    &[bump],
+                ],
+            )?;
+            ReferralRewards {
+                owner,
+                mint,
+                bump,
+                accrued: 0,
+                total_claimed: 0,
+                created_at: now,
+                updated_at: now,
+            }
+        } else {
+            if rewards.owner != &crate::ID {
+                return err!(anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+            }
+            ReferralRewards::try_deserialize(&mut &rewards.try_borrow_data()?[..])?
+        };
+        state.accrue(amount, now).ok_or(ErrorCode::Overflow)?;
+        state.try_serialize(&mut &mut rewards.try_borrow_mut_data()?[..])?;
+
+        Ok(state.accrued)
+    }
+    ///
+    /// Add a swap to the referral stats of an owner, created on the first swap
+    ///
+    fn record_stats(
+        &self,
+        stats: &AccountInfo<'info>,
+        owner: Pubkey,
+        volume: u64,
+        rewards: u64,
+        now: i64,
+    ) -> Result<()> {
+        let authority = self.authority.to_account_info();
+        let system_program = self.system_program.to_account_info();
+        let (address, bump) = Pubkey::find_program_address(
+            &["referral_stats".as_bytes(), &owner.to_bytes()],
+            &crate::ID,
+        );
+        if stats.key() != address {
+            return err!(anchor_lang::error::ErrorCode::ConstraintSeeds);
+        }
+
+        let mut state = if stats.data_is_empty() {
+            create_pda_account(
+                stats,
+                &authority,
+                &system_program,
+                ReferralStats::space(0),
+                &["referral_stats".as_bytes(), &owner.to_bytes(), &[bump]],
+            )?;
+            ReferralStats {
+                owner,
+                bump,
+                total_referees: 0,
+                updated_at: now,
+                mints: vec![],
+            }
+        } else {
+            if stats.owner != &crate::ID {
+                return err!(anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+            }
+            ReferralStats::try_deserialize(&mut &stats.try_borrow_data()?[..])?
+        };
+        let grown = state
+            .record(self.bid_mint.key(), volume, rewards, now)
+            .ok_or(ErrorCode::Overflow)?;
+        if grown {
+            realloc_account(
+                stats,
+                &authority,
+                &system_program,
+                ReferralStats::space(state.mints.len()),
+            )?;
+        }
+        state.try_serialize(&mut &mut stats.try_borrow_mut_data()?[..])?;
+
+        Ok(())
+    }
 }
@@ -10,26 +10,27 @@ pub struct TransferOwnershipEvent {
     pub updated_at: i64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct TransferOwnership<'info> {
     /// Authority
     pub authority: Signer<'info>,
     /// Pool
     #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
-    pub pool: Account<'info, Pool>,
+    pub pool: AccountLoader<'info, Pool>,
 }
 
 impl TransferOwnership<'_> {
     pub fn invoke(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
+        let mut pool = ctx.accounts.pool.load_mut()?;
 
         pool.authority = new_owner;
         pool.updated_at = Clock::get()?.unix_timestamp;
 
-        emit!(TransferOwnershipEvent {
+        emit_cpi!(TransferOwnershipEvent {
             authority: ctx.accounts.authority.key(),
             new_owner,
-            pool: pool.key(),
+            pool: ctx.accounts.pool.key(),
             updated_at: pool.updated_at
         });
 
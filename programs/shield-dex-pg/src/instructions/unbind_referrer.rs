@@ -0,0 +1,59 @@
+use crate::errors::ErrorCode;
+use crate::schema::{referer::Referrer, referral_stats::ReferralStats};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UnbindReferrerEvent {
+    pub referee: Pubkey,
+    pub referrer: Pubkey,
+    pub pool: Pubkey,
+    pub owner: Pubkey,
+    pub unbound_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UnbindReferrer<'info> {
+    /// Referee
+    pub authority: Signer<'info>,
+    /// Referrer
+    #[account(
+      mut,
+      constraint = referrer.referee == authority.key() @ ErrorCode::Unauthorized,
+      constraint = referrer.is_bound() @ ErrorCode::InvalidState
+    )]
+    pub referrer: Account<'info, Referrer>,
+    /// CHECK: Stats of the referrer, uncounting the referee
+    #[account(
+      mut,
+      seeds = ["referral_stats".as_bytes(), &referrer.owner.to_bytes()],
+      bump
+    )]
+    pub referral_stats: AccountInfo<'info>,
+}
+
+impl UnbindReferrer<'_> {
+    ///
+    /// Stop paying the referrer, the account is kept so that the cooldown still applies
+    ///
+    pub fn invoke(ctx: Context<UnbindReferrer>) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        ReferralStats::remove_referee_from(&ctx.accounts.referral_stats, now)?;
+
+        let referrer = &mut ctx.accounts.referrer;
+        let owner = referrer.owner;
+
+        referrer.owner = Pubkey::default();
+        referrer.parent = Pubkey::default();
+
+        emit_cpi!(UnbindReferrerEvent {
+            referee: ctx.accounts.authority.key(),
+            referrer: referrer.key(),
+            pool: referrer.pool,
+            owner,
+            unbound_at: now,
+        });
+
+        Ok(())
+    }
+}
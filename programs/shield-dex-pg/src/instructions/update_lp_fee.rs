@@ -1,42 +1,79 @@
-use crate::constants::MAXIMUM_FEE;
+use crate::constants::ROLE_FEE_MANAGER;
 use crate::errors::ErrorCode;
-use crate::schema::pool::*;
+use crate::schema::{
+    access_control::AccessControl,
+    pending_change::{ParamChange, PendingChange},
+    platform_config::PlatformConfig,
+    pool::*,
+};
 use anchor_lang::prelude::*;
 
 #[event]
 pub struct UpdateLPFeeEvent {
     pub authority: Pubkey,
     pub pool: Pubkey,
+    pub old_lp_fee: u64,
     pub lp_fee: u64,
     pub updated_at: i64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateLPFee<'info> {
-    /// Authority
+    /// Owner or fee manager
+    #[account(mut, constraint = pool.load()?.is_granted(access_control.as_deref(), authority.key(), ROLE_FEE_MANAGER) @ ErrorCode::Unauthorized)]
     pub authority: Signer<'info>,
     /// Pool
-    #[account(mut, has_one =  authority @ ErrorCode::Unauthorized)]
-    pub pool: Account<'info, Pool>,
+    pub pool: AccountLoader<'info, Pool>,
+    /// Access control
+    #[account(
+      has_one = pool @ ErrorCode::UnmatchPool,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Option<Account<'info, AccessControl>>,
+    /// Which config the pool belongs to.
+    #[account(address = pool.load()?.tax @ ErrorCode::InvalidPlatformConfig)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// Pending change
+    #[account(
+      init,
+      payer = authority,
+      space = PendingChange::LEN,
+      seeds = [
+        "pending_change".as_bytes(),
+        &pool.key().to_bytes(),
+        &[ParamChange::LP_FEE]
+      ],
+      bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    /// System programs
+    pub system_program: Program<'info, System>,
 }
 
 impl UpdateLPFee<'_> {
-    pub fn invoke(ctx: Context<UpdateLPFee>, lp_fee: u64) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
+    ///
+    /// Queue the lp fee change, executable by anyone after `eta`
+    ///
+    pub fn invoke(ctx: Context<UpdateLPFee>, lp_fee: u64, eta: i64) -> Result<()> {
+        let platform_config = &ctx.accounts.platform_config;
 
-        if lp_fee > MAXIMUM_FEE {
+        if !platform_config.is_valid_lp_fee(lp_fee) {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if !platform_config.is_valid_eta(eta, Clock::get()?.unix_timestamp) {
             return err!(ErrorCode::InvalidParams);
         }
 
-        pool.lp_fee = lp_fee;
-        pool.updated_at = Clock::get()?.unix_timestamp;
-
-        emit!(UpdateLPFeeEvent {
-            authority: ctx.accounts.authority.key(),
-            pool: pool.key(),
-            lp_fee,
-            updated_at: pool.updated_at
-        });
+        let event = PendingChange::propose(
+            &mut ctx.accounts.pending_change,
+            ctx.accounts.pool.key(),
+            ctx.accounts.authority.key(),
+            ParamChange::LpFee(lp_fee),
+            eta,
+        )?;
+        emit_cpi!(event);
 
         Ok(())
     }
@@ -0,0 +1,105 @@
+use crate::{constants::MAX_LP_MINT_URI_LEN, errors::ErrorCode, schema::pool::Pool};
+use anchor_lang::prelude::*;
+use anchor_lang::{solana_program, system_program};
+use anchor_spl::{
+    token_2022::{
+        self,
+        spl_token_2022::{
+            extension::{BaseStateWithExtensions, StateWithExtensions},
+            state::Mint,
+        },
+    },
+    token_interface,
+};
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+
+#[event]
+pub struct UpdateLpUriEvent {
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub lp_mint: Pubkey,
+    pub uri: String,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateLpUri<'info> {
+    /// Authority, paying the rent of a longer uri
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// Pool
+    #[account(
+      has_one = authority @ ErrorCode::Unauthorized,
+      has_one = lp_mint @ ErrorCode::UnmatchPool
+    )]
+    pub pool: AccountLoader<'info, Pool>,
+    /// LP Mint, holding its metadata
+    #[account(mut, mint::token_program = token_2022_program)]
+    pub lp_mint: Box<InterfaceAccount<'info, token_interface::Mint>>,
+    /// CHECK: The pool escrow, update authority of the metadata
+    #[account(seeds = ["escrow".as_bytes(), &pool.key().to_bytes()], bump)]
+    pub escrow: AccountInfo<'info>,
+    /// System programs
+    pub token_2022_program: Program<'info, token_2022::Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+impl UpdateLpUri<'_> {
+    pub fn invoke(ctx: Context<UpdateLpUri>, uri: String) -> Result<()> {
+        let lp_mint = ctx.accounts.lp_mint.to_account_info();
+        let seeds: &[&[&[u8]]] = &[&[
+            "escrow".as_ref(),
+            &ctx.accounts.pool.key().to_bytes(),
+            &[ctx.bumps.escrow],
+        ]];
+
+        if uri.len() > MAX_LP_MINT_URI_LEN {
+            return err!(ErrorCode::InvalidParams);
+        }
+
+        // Token-2022 resizes the mint for the new uri, so the rent is topped up beforehand
+        let new_len = {
+            let data = lp_mint.try_borrow_data()?;
+            let state = StateWithExtensions::<Mint>::unpack(&data)?;
+            let mut metadata = state.get_variable_len_extension::<TokenMetadata>()?;
+            metadata.uri = uri.clone();
+            state.try_get_new_account_len(&metadata)?
+        };
+        let rent = Rent::get()?.minimum_balance(new_len);
+        if rent > lp_mint.lamports() {
+            system_program::transfer(
+                CpiContext::new(
+                    ctx.accounts.system_program.to_account_info(),
+                    system_program::Transfer {
+                        from: ctx.accounts.authority.to_account_info(),
+                        to: lp_mint.clone(),
+                    },
+                ),
+                rent - lp_mint.lamports(),
+            )?;
+        }
+
+        solana_program::program::invoke_signed(
+            &spl_token_metadata_interface::instruction::update_field(
+                &token_2022::ID,
+                &lp_mint.key(),
+                &ctx.accounts.escrow.key(),
+                Field::Uri,
+                uri.clone(),
+            ),
+            &[lp_mint.clone(), ctx.accounts.escrow.to_account_info()],
+            seeds,
+        )?;
+
+        emit_cpi!(UpdateLpUriEvent {
+            authority: ctx.accounts.authority.key(),
+            pool: ctx.accounts.pool.key(),
+            lp_mint: lp_mint.key(),
+            uri,
+            updated_at: Clock::get()?.unix_timestamp
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,54 @@
+use crate::constants::ROLE_POOL_MANAGER;
+use crate::errors::ErrorCode;
+use crate::schema::{access_control::AccessControl, pool::*};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdatePoolGuardianEvent {
+    pub authority: Pubkey,
+    pub pool: Pubkey,
+    pub old_guardian: Pubkey,
+    pub guardian: Pubkey,
+    pub updated_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdatePoolGuardian<'info> {
+    /// Owner or pool manager
+    pub authority: Signer<'info>,
+    /// Pool
+    #[account(mut, constraint = pool.load()?.is_granted(access_control.as_deref(), authority.key(), ROLE_POOL_MANAGER) @ ErrorCode::Unauthorized)]
+    pub pool: AccountLoader<'info, Pool>,
+    /// Access control
+    #[account(
+      has_one = pool @ ErrorCode::UnmatchPool,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Option<Account<'info, AccessControl>>,
+}
+
+impl UpdatePoolGuardian<'_> {
+    ///
+    /// Set the guardian to the default pubkey to revoke it
+    ///
+    pub fn invoke(ctx: Context<UpdatePoolGuardian>, guardian: Pubkey) -> Result<()> {
+        let mut pool = ctx.accounts.pool.load_mut()?;
+
+        let old_guardian = pool.guardian;
+
+        pool.guardian = guardian;
+        pool.updated_at = Clock::get()?.unix_timestamp;
+
+        emit_cpi!(UpdatePoolGuardianEvent {
+            authority: ctx.accounts.authority.key(),
+            pool: ctx.accounts.pool.key(),
+            old_guardian,
+            guardian,
+            updated_at: pool.updated_at
+        });
+
+        Ok(())
+    }
+}
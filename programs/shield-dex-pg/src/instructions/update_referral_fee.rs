@@ -1,42 +1,79 @@
-use crate::constants::MAXIMUM_FEE;
+use crate::constants::ROLE_REFERRAL_MANAGER;
 use crate::errors::ErrorCode;
-use crate::schema::pool::*;
+use crate::schema::{
+    access_control::AccessControl,
+    pending_change::{ParamChange, PendingChange},
+    platform_config::PlatformConfig,
+    pool::*,
+};
 use anchor_lang::prelude::*;
 
 #[event]
 pub struct UpdateReferralFeeEvent {
     pub authority: Pubkey,
     pub pool: Pubkey,
+    pub old_referral_fee: u64,
     pub referral_fee: u64,
     pub updated_at: i64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateReferralFee<'info> {
-    /// Authority
+    /// Owner or referral manager
+    #[account(mut, constraint = pool.load()?.is_granted(access_control.as_deref(), authority.key(), ROLE_REFERRAL_MANAGER) @ ErrorCode::Unauthorized)]
     pub authority: Signer<'info>,
     /// Pool
-    #[account(mut, has_one =  authority @ ErrorCode::Unauthorized)]
-    pub pool: Account<'info, Pool>,
+    pub pool: AccountLoader<'info, Pool>,
+    /// Access control
+    #[account(
+      has_one = pool @ ErrorCode::UnmatchPool,
+      seeds = ["access_control".as_bytes(), &pool.key().to_bytes()],
+      bump
+    )]
+    pub access_control: Option<Account<'info, AccessControl>>,
+    /// Which config the pool belongs to.
+    #[account(address = pool.load()?.tax @ ErrorCode::InvalidPlatformConfig)]
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// Pending change
+    #[account(
+      init,
+      payer = authority,
+      space = PendingChange::LEN,
+      seeds = [
+        "pending_change".as_bytes(),
+        &pool.key().to_bytes(),
+        &[ParamChange::REFERRAL_FEE]
+      ],
+      bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    /// System programs
+    pub system_program: Program<'info, System>,
 }
 
 impl UpdateReferralFee<'_> {
-    pub fn invoke(ctx: Context<UpdateReferralFee>, referral_fee: u64) -> Result<()> {
-        let pool = &mut ctx.accounts.pool;
+    ///
+    /// Queue the referral fee change, executable by anyone after `eta`
+    ///
+    pub fn invoke(ctx: Context<UpdateReferralFee>, referral_fee: u64, eta: i64) -> Result<()> {
+        let platform_config = &ctx.accounts.platform_config;
 
-        if referral_fee > MAXIMUM_FEE {
+        if !platform_config.is_valid_referral_fee(referral_fee) {
+            return err!(ErrorCode::InvalidParams);
+        }
+        if !platform_config.is_valid_eta(eta, Clock::get()?.unix_timestamp) {
             return err!(ErrorCode::InvalidParams);
         }
 
-        pool.referral_fee = referral_fee;
-        pool.updated_at = Clock::get()?.unix_timestamp;
-
-        emit!(UpdateReferralFeeEvent {
-            authority: ctx.accounts.authority.key(),
-            pool: pool.key(),
-            referral_fee,
-            updated_at: pool.updated_at
-        });
+        let event = PendingChange::propose(
+            &mut ctx.accounts.pending_change,
+            ctx.accounts.pool.key(),
+            ctx.accounts.authority.key(),
+            ParamChange::ReferralFee(referral_fee),
+            eta,
+        )?;
+        emit_cpi!(event);
 
         Ok(())
     }
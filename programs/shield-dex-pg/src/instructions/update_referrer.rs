@@ -0,0 +1,134 @@
+use crate::errors::ErrorCode;
+use crate::schema::{
+    platform_config::PlatformConfig, referer::Referrer, referral_stats::ReferralStats,
+};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct UpdateReferrerEvent {
+    pub referee: Pubkey,
+    pub referrer: Pubkey,
+    pub pool: Pubkey,
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub parent: Pubkey,
+    pub bound_at: i64,
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(referer_address: Pubkey)]
+pub struct UpdateReferrer<'info> {
+    /// Referee
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    /// CHECK: Pool of a pool-scoped binding, possibly closed, none for a global binding
+    #[account(address = referrer.pool @ ErrorCode::UnmatchPool)]
+    pub pool: Option<UncheckedAccount<'info>>,
+    /// Config holding the referrer cooldown
+    pub platform_config: Account<'info, PlatformConfig>,
+    /// Referrer
+    #[account(
+      mut,
+      constraint = referrer.referee == authority.key() @ ErrorCode::Unauthorized,
+      constraint = referrer.owner != referer_address @ ErrorCode::InvalidReferer
+    )]
+    pub referrer: Account<'info, Referrer>,
+    /// CHECK: Binding of the new referrer itself in the same scope, empty at the top tier
+    #[account(
+      seeds = [
+        "referrer".as_bytes(),
+        &referrer.pool.to_bytes(),
+        &referer_address.to_bytes()
+      ],
+      bump
+    )]
+    pub parent: AccountInfo<'info>,
+    /// Stats of the referrer, counting its referees
+    #[account(
+      init_if_needed,
+      payer = authority,
+      space = ReferralStats::space(0),
+      seeds = ["referral_stats".as_bytes(), &referer_address.to_bytes()],
+      bump
+    )]
+    pub referral_stats: Account<'info, ReferralStats>,
+    /// CHECK: Stats of the current referrer, uncounting the referee
+    #[account(
+      mut,
+      seeds = ["referral_stats".as_bytes(), &referrer.owner.to_bytes()],
+      bump
+    )]
+    pub previous_referral_stats: AccountInfo<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl UpdateReferrer<'_> {
+    ///
+    /// Rebind to another referrer once the cooldown since the last binding has elapsed
+    /// The remaining accounts are checked for cycles as in `create_referrer`
+    ///
+    pub fn invoke(ctx: Context<UpdateReferrer>, referer_address: Pubkey) -> Result<()> {
+        let platform_config = &ctx.accounts.platform_config;
+        let referee = ctx.accounts.authority.key();
+        let now = Clock::get()?.unix_timestamp;
+        let pool_config = ctx
+            .accounts
+            .referrer
+            .pool_config(ctx.accounts.pool.as_deref())?;
+
+        if !ctx
+            .accounts
+            .referrer
+            .is_cooldown_config(pool_config, platform_config)
+        {
+            return err!(ErrorCode::InvalidPlatformConfig);
+        }
+        if !ctx
+            .accounts
+            .referrer
+            .is_cooled_down(platform_config.referrer_cooldown, now)
+        {
+            return err!(ErrorCode::ReferrerCooldown);
+        }
+        let parent = Referrer::load_parent(&ctx.accounts.parent)?;
+        Referrer::check_cycle(
+            referer_address,
+            referee,
+            parent.as_ref(),
+            ctx.remaining_accounts,
+        )?;
+
+        if ctx.accounts.referrer.is_bound() {
+            ReferralStats::remove_referee_from(&ctx.accounts.previous_referral_stats, now)?;
+        }
+        let referral_stats = &mut ctx.accounts.referral_stats;
+        if !referral_stats.is_initialized() {
+            referral_stats.owner = referer_address;
+            referral_stats.bump = ctx.bumps.referral_stats;
+        }
+        referral_stats.add_referee(now).ok_or(ErrorCode::Overflow)?;
+
+        let referrer = &mut ctx.accounts.referrer;
+        let old_owner = referrer.owner;
+
+        referrer.owner = referer_address;
+        referrer.parent = parent
+            .map(|_| ctx.accounts.parent.key())
+            .unwrap_or_default();
+        referrer.bound_at = now;
+
+        emit_cpi!(UpdateReferrerEvent {
+            referee,
+            referrer: referrer.key(),
+            pool: referrer.pool,
+            old_owner,
+            new_owner: referer_address,
+            parent: referrer.parent,
+            bound_at: now,
+        });
+
+        Ok(())
+    }
+}
@@ -1,42 +1,71 @@
 use crate::constants::MAXIMUM_FEE;
 use crate::errors::ErrorCode;
-use crate::schema::platform_config::PlatformConfig;
+use crate::schema::{
+    pending_change::{ParamChange, PendingChange},
+    platform_config::PlatformConfig,
+    program_state::ProgramState,
+};
 use anchor_lang::prelude::*;
 
 #[event]
 pub struct UpdateTaxEvent {
     pub authority: Pubkey,
+    pub old_tax: u64,
     pub new_tax: u64,
     pub platform_config: Pubkey,
     pub updated_at: i64,
 }
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateTax<'info> {
     /// Authority
+    #[account(mut, address = program_state.admin @ ErrorCode::Unauthorized)]
     pub authority: Signer<'info>,
-    /// Pool
-    #[account(mut, address = crate::admin::id() @ ErrorCode::Unauthorized)]
+    /// Program state stores the admin
+    #[account(seeds = ["program_state".as_bytes()], bump = program_state.bump)]
+    pub program_state: Account<'info, ProgramState>,
+    /// Platform config
     pub platform_config: Account<'info, PlatformConfig>,
+    /// Pending change
+    #[account(
+      init,
+      payer = authority,
+      space = PendingChange::LEN,
+      seeds = [
+        "pending_change".as_bytes(),
+        &platform_config.key().to_bytes(),
+        &[ParamChange::TAX]
+      ],
+      bump
+    )]
+    pub pending_change: Account<'info, PendingChange>,
+    /// System programs
+    pub system_program: Program<'info, System>,
 }
 
 impl UpdateTax<'_> {
-    pub fn invoke(ctx: Context<UpdateTax>, tax: u64) -> Result<()> {
-        let platform_config = &mut ctx.accounts.platform_config;
+    ///
+    /// Queue the tax change, executable by anyone after `eta`
+    ///
+    pub fn invoke(ctx: Context<UpdateTax>, tax: u64, eta: i64) -> Result<()> {
+        let platform_config = &ctx.accounts.platform_config;
 
         if tax > MAXIMUM_FEE {
             return err!(ErrorCode::InvalidParams);
         }
+        if !platform_config.is_valid_eta(eta, Clock::get()?.unix_timestamp) {
+            return err!(ErrorCode::InvalidParams);
+        }
 
-        platform_config.tax = tax;
-        platform_config.updated_at = Clock::get()?.unix_timestamp;
-
-        emit!(UpdateTaxEvent {
-            authority: ctx.accounts.authority.key(),
-            platform_config: platform_config.key(),
-            new_tax: tax,
-            updated_at: platform_config.updated_at
-        });
+        let event = PendingChange::propose(
+            &mut ctx.accounts.pending_change,
+            platform_config.key(),
+            ctx.accounts.authority.key(),
+            ParamChange::Tax(tax),
+            eta,
+        )?;
+        emit_cpi!(event);
 
         Ok(())
     }
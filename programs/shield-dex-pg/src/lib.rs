@@ -1,4 +1,6 @@
 use anchor_lang::prelude::*;
+use constants::MAX_REFERRAL_TIERS;
+use schema::{platform_config::FeeTier, token_registry::TokenStatus};
 
 pub mod constants;
 pub mod errors;
@@ -16,12 +18,10 @@ pub mod admin {
     declare_id!("CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1");
 }
 
-pub mod create_pool_fee_reveiver {
+pub mod metaplex {
     use anchor_lang::prelude::declare_id;
-    // #[cfg(feature = "devnet")]
-    // declare_id!("G11FKBRaAkHAKuLCgLM6K6NUc9rTjPAznRCjZifrTQe2");
-    // #[cfg(not(feature = "devnet"))]
-    declare_id!("CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1");
+    // Token metadata program, naming the mints without Token-2022 metadata
+    declare_id!("metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s");
 }
 
 #[program]
@@ -29,9 +29,18 @@ pub mod shield_dex_pg {
     use super::*;
 
     pub use instructions::{
-        add_liquidity::*, create_platform_config::*, create_referrer::*, distribute_lp_fee::*,
-        initialize::*, pause::*, remove_liquidity::*, resume::*, swap::*, transfer_ownership::*,
-        update_lp_fee::*, update_platform_config::*, update_referral_fee::*, update_tax::*,
+        add_liquidity::*, cancel_change::*, cancel_pool::*, claim_referral_rewards::*,
+        close_pool::*, close_referrer::*, create_platform_config::*, create_referrer::*,
+        create_referrer_by_code::*, distribute_lp_fee::*, execute_change::*, grant_role::*,
+        initialize::*, initialize_program_state::*, lean_swap::*, migrate_platform_config::*,
+        migrate_pool::*, migrate_referrer::*, pause::*, register_code::*, remove_liquidity::*,
+        resume::*, revoke_role::*, set_emergency_pause::*, set_token_status::*, swap::*,
+        transfer_ownership::*, unbind_referrer::*, update_admin::*, update_create_pool_fee::*,
+        update_fee_receiver::*, update_guardian::*, update_lp_fee::*, update_lp_uri::*,
+        update_mint_policy::*, update_platform_config::*, update_pool_guardian::*,
+        update_referral_fee::*, update_referral_tiers::*, update_referrer::*,
+        update_referrer_cooldown::*, update_tax::*, update_timelock_delay::*,
+        update_token_policy::*,
     };
 
     pub fn initialize(
@@ -39,10 +48,11 @@ pub mod shield_dex_pg {
         a: u64,
         b: u64,
         referral_fee: u64,
-        sol_amount_for_custom_fee: u64,
-        fee: u64,
+        fee: Option<u64>,
+        index_page_a: u32,
+        index_page_b: u32,
     ) -> Result<()> {
-        Initialize::invoke(ctx, a, b, referral_fee, sol_amount_for_custom_fee, fee)
+        Initialize::invoke(ctx, a, b, referral_fee, fee, index_page_a, index_page_b)
     }
 
     pub fn add_liquidity(ctx: Context<AddLiquidity>, a: u64, b: u64) -> Result<()> {
@@ -53,40 +63,185 @@ pub mod shield_dex_pg {
         RemoveLiquidity::invoke(ctx, lp)
     }
 
-    pub fn swap(ctx: Context<Swap>, bid_amount: u64, limit: u64) -> Result<u64> {
+    pub fn swap<'info>(
+        ctx: Context<'_, '_, '_, 'info, Swap<'info>>,
+        bid_amount: u64,
+        limit: u64,
+    ) -> Result<u64> {
         Swap::invoke(ctx, bid_amount, limit)
     }
 
-    pub fn update_fee(ctx: Context<UpdateLPFee>, fee: u64) -> Result<()> {
-        UpdateLPFee::invoke(ctx, fee)
+    pub fn lean_swap(ctx: Context<LeanSwap>, bid_amount: u64, limit: u64) -> Result<u64> {
+        LeanSwap::invoke(ctx, bid_amount, limit)
+    }
+
+    pub fn update_fee(ctx: Context<UpdateLPFee>, fee: u64, eta: i64) -> Result<()> {
+        UpdateLPFee::invoke(ctx, fee, eta)
     }
 
-    pub fn update_referral_fee(ctx: Context<UpdateReferralFee>, fee: u64) -> Result<()> {
-        UpdateReferralFee::invoke(ctx, fee)
+    pub fn update_referral_fee(ctx: Context<UpdateReferralFee>, fee: u64, eta: i64) -> Result<()> {
+        UpdateReferralFee::invoke(ctx, fee, eta)
     }
 
-    pub fn update_tax(ctx: Context<UpdateTax>, tax: u64) -> Result<()> {
-        UpdateTax::invoke(ctx, tax)
+    pub fn update_tax(ctx: Context<UpdateTax>, tax: u64, eta: i64) -> Result<()> {
+        UpdateTax::invoke(ctx, tax, eta)
+    }
+
+    pub fn execute_change(ctx: Context<ExecuteChange>) -> Result<()> {
+        ExecuteChange::invoke(ctx)
+    }
+
+    pub fn cancel_change(ctx: Context<CancelChange>) -> Result<()> {
+        CancelChange::invoke(ctx)
     }
 
     pub fn transfer_ownership(ctx: Context<TransferOwnership>, new_owner: Pubkey) -> Result<()> {
         TransferOwnership::invoke(ctx, new_owner)
     }
 
-    pub fn pause(ctx: Context<Pause>) -> Result<()> {
-        Pause::invoke(ctx)
+    pub fn grant_role(ctx: Context<GrantRole>, grantee: Pubkey, roles: u8) -> Result<()> {
+        GrantRole::invoke(ctx, grantee, roles)
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRole>, grantee: Pubkey, roles: u8) -> Result<()> {
+        RevokeRole::invoke(ctx, grantee, roles)
+    }
+
+    pub fn update_pool_guardian(ctx: Context<UpdatePoolGuardian>, guardian: Pubkey) -> Result<()> {
+        UpdatePoolGuardian::invoke(ctx, guardian)
+    }
+
+    pub fn pause(ctx: Context<Pause>, flags: u8) -> Result<()> {
+        Pause::invoke(ctx, flags)
+    }
+
+    pub fn resume(ctx: Context<Resume>, flags: u8) -> Result<()> {
+        Resume::invoke(ctx, flags)
+    }
+
+    pub fn cancel_pool(ctx: Context<CancelPool>) -> Result<()> {
+        CancelPool::invoke(ctx)
+    }
+
+    pub fn update_lp_uri(ctx: Context<UpdateLpUri>, uri: String) -> Result<()> {
+        UpdateLpUri::invoke(ctx, uri)
+    }
+
+    pub fn migrate_pool(
+        ctx: Context<MigratePool>,
+        index_page_a: u32,
+        index_page_b: u32,
+    ) -> Result<()> {
+        MigratePool::invoke(ctx, index_page_a, index_page_b)
+    }
+
+    pub fn migrate_platform_config(ctx: Context<MigratePlatformConfig>) -> Result<()> {
+        MigratePlatformConfig::invoke(ctx)
+    }
+
+    pub fn migrate_referrer(ctx: Context<MigrateReferrer>) -> Result<()> {
+        MigrateReferrer::invoke(ctx)
+    }
+
+    pub fn close_pool(ctx: Context<ClosePool>, index_page_a: u32, index_page_b: u32) -> Result<()> {
+        ClosePool::invoke(ctx, index_page_a, index_page_b)
+    }
+
+    pub fn set_token_status(ctx: Context<SetTokenStatus>, status: TokenStatus) -> Result<()> {
+        SetTokenStatus::invoke(ctx, status)
+    }
+
+    pub fn update_token_policy(
+        ctx: Context<UpdateTokenPolicy>,
+        strict_token_list: bool,
+    ) -> Result<()> {
+        UpdateTokenPolicy::invoke(ctx, strict_token_list)
+    }
+
+    pub fn update_mint_policy(
+        ctx: Context<UpdateMintPolicy>,
+        rejected_mint_risks: u8,
+    ) -> Result<()> {
+        UpdateMintPolicy::invoke(ctx, rejected_mint_risks)
+    }
+
+    pub fn initialize_program_state(ctx: Context<InitializeProgramState>) -> Result<()> {
+        InitializeProgramState::invoke(ctx)
+    }
+
+    pub fn create_platform_config(
+        ctx: Context<CreatePlatformConfig>,
+        fee_tier: FeeTier,
+        fee_receiver: Pubkey,
+        timelock_delay: i64,
+    ) -> Result<()> {
+        CreatePlatformConfig::invoke(ctx, fee_tier, fee_receiver, timelock_delay)
     }
 
-    pub fn resume(ctx: Context<Resume>) -> Result<()> {
-        Resume::invoke(ctx)
+    pub fn update_platform_config(
+        ctx: Context<UpdatePlatformConfig>,
+        fee_tier: FeeTier,
+        eta: i64,
+    ) -> Result<()> {
+        UpdatePlatformConfig::invoke(ctx, fee_tier, eta)
+    }
+
+    pub fn update_timelock_delay(
+        ctx: Context<UpdateTimelockDelay>,
+        timelock_delay: i64,
+        eta: i64,
+    ) -> Result<()> {
+        UpdateTimelockDelay::invoke(ctx, timelock_delay, eta)
     }
 
-    pub fn create_platform_config(ctx: Context<CreatePlatformConfig>, tax: u64) -> Result<()> {
-        CreatePlatformConfig::invoke(ctx, tax)
+    pub fn update_admin(ctx: Context<UpdateAdmin>, admin: Pubkey) -> Result<()> {
+        UpdateAdmin::invoke(ctx, admin)
     }
 
-    pub fn update_platform_config(ctx: Context<UpdatePlatformConfig>, tax: u64) -> Result<()> {
-        UpdatePlatformConfig::invoke(ctx, tax)
+    pub fn update_fee_receiver(
+        ctx: Context<UpdateFeeReceiver>,
+        fee_receiver: Pubkey,
+    ) -> Result<()> {
+        UpdateFeeReceiver::invoke(ctx, fee_receiver)
+    }
+
+    pub fn update_guardian(ctx: Context<UpdateGuardian>, guardian: Pubkey) -> Result<()> {
+        UpdateGuardian::invoke(ctx, guardian)
+    }
+
+    pub fn set_emergency_pause(
+        ctx: Context<SetEmergencyPause>,
+        emergency_paused: bool,
+    ) -> Result<()> {
+        SetEmergencyPause::invoke(ctx, emergency_paused)
+    }
+
+    pub fn update_create_pool_fee(
+        ctx: Context<UpdateCreatePoolFee>,
+        create_pool_fee: u64,
+        custom_fee_surcharge: u64,
+        create_pool_fee_mint: Pubkey,
+    ) -> Result<()> {
+        UpdateCreatePoolFee::invoke(
+            ctx,
+            create_pool_fee,
+            custom_fee_surcharge,
+            create_pool_fee_mint,
+        )
+    }
+
+    pub fn update_referral_tiers(
+        ctx: Context<UpdateReferralTiers>,
+        referral_tier_shares: [u64; MAX_REFERRAL_TIERS - 1],
+    ) -> Result<()> {
+        UpdateReferralTiers::invoke(ctx, referral_tier_shares)
+    }
+
+    pub fn update_referrer_cooldown(
+        ctx: Context<UpdateReferrerCooldown>,
+        referrer_cooldown: i64,
+    ) -> Result<()> {
+        UpdateReferrerCooldown::invoke(ctx, referrer_cooldown)
     }
 
     pub fn distribute_lp_fee(
@@ -100,4 +255,28 @@ pub mod shield_dex_pg {
     pub fn create_referrer(ctx: Context<CreateReferrer>, referer_address: Pubkey) -> Result<()> {
         CreateReferrer::invoke(ctx, referer_address)
     }
+
+    pub fn register_code(ctx: Context<RegisterCode>, code: String) -> Result<()> {
+        RegisterCode::invoke(ctx, code)
+    }
+
+    pub fn create_referrer_by_code(ctx: Context<CreateReferrerByCode>, code: String) -> Result<()> {
+        CreateReferrerByCode::invoke(ctx, code)
+    }
+
+    pub fn update_referrer(ctx: Context<UpdateReferrer>, referer_address: Pubkey) -> Result<()> {
+        UpdateReferrer::invoke(ctx, referer_address)
+    }
+
+    pub fn unbind_referrer(ctx: Context<UnbindReferrer>) -> Result<()> {
+        UnbindReferrer::invoke(ctx)
+    }
+
+    pub fn close_referrer(ctx: Context<CloseReferrer>) -> Result<()> {
+        CloseReferrer::invoke(ctx)
+    }
+
+    pub fn claim_referral_rewards(ctx: Context<ClaimReferralRewards>) -> Result<u64> {
+        ClaimReferralRewards::invoke(ctx)
+    }
 }
@@ -0,0 +1,77 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+///
+/// Role grant struct
+///
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RoleGrant {
+    pub grantee: Pubkey,
+    pub roles: u8,
+}
+
+impl RoleGrant {
+    pub const LEN: usize = PUBKEY_SIZE + U8_SIZE;
+}
+
+///
+/// Access control struct
+/// The pool authority implicitly holds every role
+///
+#[account]
+pub struct AccessControl {
+    pub pool: Pubkey,
+    pub grants: Vec<RoleGrant>,
+}
+
+impl AccessControl {
+    pub const LEN: usize =
+        ACCOUNT_DISCRIMINATOR + PUBKEY_SIZE + VECTOR_SIZE + MAX_ROLE_GRANTS * RoleGrant::LEN;
+
+    ///
+    /// The key holds all the roles
+    ///
+    pub fn has_role(&self, key: Pubkey, roles: u8) -> bool {
+        self.grants
+            .iter()
+            .any(|grant| grant.grantee == key && grant.roles & roles == roles)
+    }
+
+    ///
+    /// Add the roles to the grantee
+    /// If the return is None, the grant list is full.
+    ///
+    pub fn grant(&mut self, grantee: Pubkey, roles: u8) -> Option<u8> {
+        if let Some(grant) = self
+            .grants
+            .iter_mut()
+            .find(|grant| grant.grantee == grantee)
+        {
+            grant.roles |= roles;
+            return Some(grant.roles);
+        }
+        if self.grants.len() >= MAX_ROLE_GRANTS {
+            return None;
+        }
+        self.grants.push(RoleGrant { grantee, roles });
+        Some(roles)
+    }
+
+    ///
+    /// Remove the roles from the grantee
+    /// The grantee is dropped once it holds no role.
+    ///
+    pub fn revoke(&mut self, grantee: Pubkey, roles: u8) -> u8 {
+        let mut remaining = 0;
+        if let Some(grant) = self
+            .grants
+            .iter_mut()
+            .find(|grant| grant.grantee == grantee)
+        {
+            grant.roles &= !roles;
+            remaining = grant.roles;
+        }
+        self.grants.retain(|grant| grant.roles != 0);
+        remaining
+    }
+}
@@ -1,3 +1,12 @@
+pub mod access_control;
+pub mod pending_change;
 pub mod platform_config;
 pub mod pool;
+pub mod pool_index;
+pub mod program_state;
 pub mod referer;
+pub mod referral_code;
+pub mod referral_rewards;
+pub mod referral_stats;
+pub mod swap_counter;
+pub mod token_registry;
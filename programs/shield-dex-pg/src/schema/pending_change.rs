@@ -0,0 +1,87 @@
+use crate::constants::*;
+use crate::schema::platform_config::FeeTier;
+use anchor_lang::prelude::*;
+
+///
+/// Timelocked parameter change
+///
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum ParamChange {
+    LpFee(u64),
+    ReferralFee(u64),
+    FeeTier(FeeTier),
+    Tax(u64),
+    TimelockDelay(i64),
+}
+
+impl ParamChange {
+    pub const LEN: usize = U8_SIZE + FeeTier::LEN;
+
+    // Kinds seeding the pending changes, so that each target has at most one pending change per kind
+    pub const LP_FEE: u8 = 0;
+    pub const REFERRAL_FEE: u8 = 1;
+    pub const FEE_TIER: u8 = 2;
+    pub const TAX: u8 = 3;
+    pub const TIMELOCK_DELAY: u8 = 4;
+
+    ///
+    /// The change targets a pool, otherwise a platform config
+    ///
+    pub fn is_pool_change(&self) -> bool {
+        matches!(self, ParamChange::LpFee(_) | ParamChange::ReferralFee(_))
+    }
+}
+
+#[event]
+pub struct ProposeChangeEvent {
+    pub proposer: Pubkey,
+    pub target: Pubkey,
+    pub pending_change: Pubkey,
+    pub change: ParamChange,
+    pub eta: i64,
+    pub created_at: i64,
+}
+
+///
+/// Pending change struct
+///
+#[account]
+pub struct PendingChange {
+    pub target: Pubkey,
+    pub proposer: Pubkey,
+    pub change: ParamChange,
+    pub eta: i64,
+    pub created_at: i64,
+}
+
+impl PendingChange {
+    pub const LEN: usize =
+        ACCOUNT_DISCRIMINATOR + PUBKEY_SIZE + PUBKEY_SIZE + ParamChange::LEN + I64_SIZE + I64_SIZE;
+
+    ///
+    /// Queue the change
+    /// Return the event announcing it, emitted by the instruction
+    ///
+    pub fn propose(
+        pending_change: &mut Account<PendingChange>,
+        target: Pubkey,
+        proposer: Pubkey,
+        change: ParamChange,
+        eta: i64,
+    ) -> Result<ProposeChangeEvent> {
+        pending_change.target = target;
+        pending_change.proposer = proposer;
+        pending_change.change = change;
+        pending_change.eta = eta;
+        pending_change.created_at = Clock::get()?.unix_timestamp;
+
+        Ok(ProposeChangeEvent {
+            proposer,
+            target,
+            pending_change: pending_change.key(),
+            change,
+            eta,
+            created_at: pending_change.created_at,
+        })
+    }
+}
@@ -1,34 +1,269 @@
 use crate::constants::*;
+use crate::schema::token_registry::TokenStatus;
 use anchor_lang::prelude::*;
 use num::ToPrimitive;
 
 ///
-/// Referrer struct
+/// Fee tier params
+///
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct FeeTier {
+    pub default_lp_fee: u64,
+    pub min_lp_fee: u64,
+    pub max_lp_fee: u64,
+    pub tax: u64,
+    pub max_referral_fee: u64,
+    pub allowed_curves: u8,
+}
+
+impl FeeTier {
+    pub const LEN: usize = U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + U64_SIZE + U8_SIZE;
+
+    ///
+    /// The fee range must be ordered and every curve must be supported
+    ///
+    pub fn is_valid(&self) -> bool {
+        self.min_lp_fee <= self.default_lp_fee
+            && self.default_lp_fee <= self.max_lp_fee
+            && self.max_lp_fee <= MAXIMUM_FEE
+            && self.tax <= MAXIMUM_FEE
+            && self.max_referral_fee <= MAXIMUM_FEE
+            && self.allowed_curves != 0
+            && self.allowed_curves & !SUPPORTED_CURVES == 0
+    }
+}
+
+///
+/// Platform config struct
+/// `referral_tier_shares` are the shares of the referral fee paid to the uplines
+/// of the direct referrer, who keeps the rest
+/// `referrer_cooldown` is the delay before a referee can rebind, the first config
+/// holds it for global bindings
+/// New fields are carved out of `reserved`
 ///
 #[account]
 pub struct PlatformConfig {
+    pub index: u16,
+    pub bump: u8,
+    pub default_lp_fee: u64,
+    pub min_lp_fee: u64,
+    pub max_lp_fee: u64,
     pub tax: u64,
+    pub max_referral_fee: u64,
+    pub allowed_curves: u8,
+    pub fee_receiver: Pubkey,
+    pub create_pool_fee: u64,
+    pub custom_fee_surcharge: u64,
+    pub create_pool_fee_mint: Pubkey,
+    pub guardian: Pubkey,
+    pub emergency_paused: bool,
+    pub timelock_delay: i64,
+    pub strict_token_list: bool,
+    pub rejected_mint_risks: u8,
     pub created_at: i64,
     pub updated_at: i64,
+    pub version: u8,
+    pub referral_tier_shares: [u64; MAX_REFERRAL_TIERS - 1],
+    pub referrer_cooldown: i64,
+    pub reserved: [u8; 40],
 }
 
 impl PlatformConfig {
-    pub const LEN: usize = ACCOUNT_DISCRIMINATOR + U64_SIZE + I64_SIZE + I64_SIZE;
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR
+        + U16_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + U8_SIZE
+        + PUBKEY_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + BOOL_SIZE
+        + I64_SIZE
+        + BOOL_SIZE
+        + U8_SIZE
+        + I64_SIZE
+        + I64_SIZE
+        + U8_SIZE
+        + U64_SIZE * (MAX_REFERRAL_TIERS - 1)
+        + I64_SIZE
+        + (RESERVED_SIZE - U64_SIZE * (MAX_REFERRAL_TIERS - 1) - I64_SIZE);
+
+    ///
+    /// Current fee tier of the config
+    ///
+    pub fn fee_tier(&self) -> FeeTier {
+        FeeTier {
+            default_lp_fee: self.default_lp_fee,
+            min_lp_fee: self.min_lp_fee,
+            max_lp_fee: self.max_lp_fee,
+            tax: self.tax,
+            max_referral_fee: self.max_referral_fee,
+            allowed_curves: self.allowed_curves,
+        }
+    }
+
+    ///
+    /// Overwrite the fee tier of the config
+    ///
+    pub fn set_fee_tier(&mut self, fee_tier: &FeeTier) {
+        self.default_lp_fee = fee_tier.default_lp_fee;
+        self.min_lp_fee = fee_tier.min_lp_fee;
+        self.max_lp_fee = fee_tier.max_lp_fee;
+        self.tax = fee_tier.tax;
+        self.max_referral_fee = fee_tier.max_referral_fee;
+        self.allowed_curves = fee_tier.allowed_curves;
+    }
+
+    ///
+    /// The change takes effect no earlier than the timelock delay from now
+    /// and no later than the longest timelock delay from now
+    ///
+    pub fn is_valid_eta(&self, eta: i64, now: i64) -> bool {
+        now.checked_add(self.timelock_delay)
+            .is_some_and(|earliest| eta >= earliest)
+            && now
+                .checked_add(MAX_TIMELOCK_DELAY)
+                .is_some_and(|latest| eta <= latest)
+    }
+
+    ///
+    /// Denylisted tokens are always rejected
+    /// In strict mode, only allowlisted tokens are accepted
+    ///
+    pub fn is_allowed_token(&self, status: TokenStatus) -> bool {
+        match status {
+            TokenStatus::Denied => false,
+            TokenStatus::Allowed => true,
+            TokenStatus::Unlisted => !self.strict_token_list,
+        }
+    }
+
+    ///
+    /// The lp fee is within the allowed range of the tier
+    ///
+    pub fn is_valid_lp_fee(&self, lp_fee: u64) -> bool {
+        self.min_lp_fee <= lp_fee && lp_fee <= self.max_lp_fee
+    }
+
+    ///
+    /// The referral fee does not exceed the cap of the tier
+    ///
+    pub fn is_valid_referral_fee(&self, referral_fee: u64) -> bool {
+        referral_fee <= self.max_referral_fee
+    }
+
+    ///
+    /// The upline shares do not exceed the whole referral fee
+    ///
+    pub fn is_valid_referral_tier_shares(shares: &[u64]) -> bool {
+        shares
+            .iter()
+            .try_fold(0u64, |sum, share| sum.checked_add(*share))
+            .is_some_and(|sum| sum.to_u128().is_some_and(|sum| sum <= PRECISION_U128))
+    }
+
+    ///
+    /// Estimate the share of a referral fee paid to an upper tier, from 1
+    ///
+    pub fn calc_referral_tier_share(&self, referral_amount: u64, tier: usize) -> Option<u64> {
+        referral_amount
+            .to_u128()?
+            .checked_mul(
+                self.referral_tier_shares
+                    .get(tier.checked_sub(1)?)?
+                    .to_u128()?,
+            )?
+            .checked_div(PRECISION_U128)?
+            .to_u64()
+    }
+
+    ///
+    /// The curve is allowed by the tier
+    ///
+    pub fn is_allowed_curve(&self, curve: u8) -> bool {
+        self.allowed_curves & curve == curve
+    }
 
     ///
     /// Estimate tax amount
     ///
     pub fn calc_tax(&self, ask_amount: u64) -> Option<u64> {
-        msg!(
-            "ask_amount: {} self.tax: {} :ss {}",
-            ask_amount,
-            self.tax,
-            PRECISION_U128
-        );
         ask_amount
             .to_u128()?
             .checked_mul(self.tax.to_u128()?)?
             .checked_div(PRECISION_U128)?
             .to_u64()
     }
+
+    ///
+    /// The pool creation fee is paid in SOL when no fee mint is configured
+    ///
+    pub fn is_native_create_pool_fee(&self) -> bool {
+        self.create_pool_fee_mint == Pubkey::default()
+    }
+
+    ///
+    /// Estimate the pool creation fee
+    /// The surcharge applies on top of the base fee when the lp fee is customized
+    ///
+    pub fn calc_create_pool_fee(&self, lp_fee: u64) -> Option<u64> {
+        if lp_fee > CUSTOMED_FEE_BOUND {
+            return self.create_pool_fee.checked_add(self.custom_fee_surcharge);
+        }
+        Some(self.create_pool_fee)
+    }
+}
+
+///
+/// Platform config layout of the baseline program, a keypair account holding the tax only
+///
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PlatformConfigV0 {
+    pub tax: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl PlatformConfigV0 {
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR + U64_SIZE + I64_SIZE + I64_SIZE;
+
+    ///
+    /// Convert to the current layout under the legacy index
+    /// The fee tier keeps the tax and leaves the fees open, the admin receives the fees as in the baseline
+    /// Changes get the shortest timelock
+    /// The config has no bump as it is not a PDA
+    ///
+    pub fn migrate(self) -> PlatformConfig {
+        PlatformConfig {
+            index: LEGACY_PLATFORM_CONFIG_INDEX,
+            bump: 0,
+            default_lp_fee: 0,
+            min_lp_fee: 0,
+            max_lp_fee: MAXIMUM_FEE,
+            tax: self.tax,
+            max_referral_fee: MAXIMUM_FEE,
+            allowed_curves: CURVE_CONSTANT_PRODUCT,
+            fee_receiver: crate::admin::id(),
+            create_pool_fee: 0,
+            custom_fee_surcharge: 0,
+            create_pool_fee_mint: Pubkey::default(),
+            guardian: Pubkey::default(),
+            emergency_paused: false,
+            timelock_delay: MIN_TIMELOCK_DELAY,
+            strict_token_list: false,
+            rejected_mint_risks: 0,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            version: PLATFORM_CONFIG_VERSION,
+            referral_tier_shares: [0; MAX_REFERRAL_TIERS - 1],
+            referrer_cooldown: 0,
+            reserved: [0; 40],
+        }
+    }
 }
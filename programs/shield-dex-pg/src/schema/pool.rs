@@ -1,29 +1,67 @@
 use crate::constants::*;
+use crate::schema::access_control::AccessControl;
 use anchor_lang::prelude::*;
 use num::{integer::Roots, ToPrimitive};
 
 ///
 /// Pool state
+/// `Paused` is only found in baseline pools, their migration turns it into pause flags
 ///
 #[repr(u8)]
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
 pub enum PoolState {
+    #[default]
     Uninitialized,
     Initialized,
     Paused,
     Canceled,
 }
-impl Default for PoolState {
-    fn default() -> Self {
-        PoolState::Uninitialized
+impl PoolState {
+    pub fn from_u8(state: u8) -> Self {
+        match state {
+            1 => PoolState::Initialized,
+            2 => PoolState::Paused,
+            3 => PoolState::Canceled,
+            _ => PoolState::Uninitialized,
+        }
     }
 }
 
 ///
 /// Pool struct
+/// Zero-copy, with the fields ordered so that `repr(C)` adds no padding
+/// New fields are carved out of `reserved`
 ///
-#[account]
+#[account(zero_copy)]
 pub struct Pool {
+    pub id: u64,
+    pub referral_fee: u64,
+    pub lp_fee: u64,
+    pub lp_fees_mint_a: u64,
+    pub lp_fees_mint_b: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub authority: Pubkey,
+    pub guardian: Pubkey,
+    pub lp_mint: Pubkey,
+    pub mint_a: Pubkey,
+    pub mint_b: Pubkey,
+    pub tax: Pubkey,
+    pub bump: u8,
+    pub state: u8,
+    pub pause_flags: u8,
+    pub risk_flags: u8,
+    pub version: u8,
+    pub padding: [u8; 3],
+    pub reserved: [u8; 64],
+}
+
+///
+/// Pool layout of the baseline program, serialized with Borsh
+/// Baseline pools are keypair accounts without id, guardian, flags or version
+///
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PoolV0 {
     pub authority: Pubkey,
     pub lp_mint: Pubkey,
     pub mint_a: Pubkey,
@@ -38,7 +76,7 @@ pub struct Pool {
     pub updated_at: i64,
 }
 
-impl Pool {
+impl PoolV0 {
     pub const LEN: usize = ACCOUNT_DISCRIMINATOR
         + PUBKEY_SIZE
         + PUBKEY_SIZE
@@ -53,18 +91,124 @@ impl Pool {
         + I64_SIZE
         + I64_SIZE;
 
+    ///
+    /// Convert to the current layout, numbering the pool and flagging the risks of its mints
+    /// The fields introduced since are left empty, the pool has no bump as it is not a PDA
+    /// The paused state becomes every pause flag on an initialized pool
+    ///
+    pub fn migrate(self, id: u64, risk_flags: u8) -> Pool {
+        let (state, pause_flags) = match self.state {
+            PoolState::Paused => (PoolState::Initialized, PAUSE_ALL),
+            state => (state, 0),
+        };
+        Pool {
+            id,
+            referral_fee: self.referral_fee,
+            lp_fee: self.lp_fee,
+            lp_fees_mint_a: self.lp_fees_mint_a,
+            lp_fees_mint_b: self.lp_fees_mint_b,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            authority: self.authority,
+            guardian: Pubkey::default(),
+            lp_mint: self.lp_mint,
+            mint_a: self.mint_a,
+            mint_b: self.mint_b,
+            tax: self.tax,
+            bump: 0,
+            state: state as u8,
+            pause_flags,
+            risk_flags,
+            version: POOL_VERSION,
+            padding: [0; 3],
+            reserved: [0; 64],
+        }
+    }
+}
+
+impl Pool {
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR + std::mem::size_of::<Pool>();
+
+    ///
+    /// Current state of the pool
+    ///
+    pub fn state(&self) -> PoolState {
+        PoolState::from_u8(self.state)
+    }
+
+    ///
+    /// Move the pool to a new state
+    ///
+    pub fn set_state(&mut self, state: PoolState) {
+        self.state = state as u8;
+    }
+
+    ///
+    /// Order a pair of mints the way pool addresses are derived
+    ///
+    pub fn sort_mints(mint_x: Pubkey, mint_y: Pubkey) -> (Pubkey, Pubkey) {
+        if mint_x < mint_y {
+            return (mint_x, mint_y);
+        }
+        (mint_y, mint_x)
+    }
+
+    ///
+    /// Find the canonical pool of a pair of mints in a platform config
+    /// The mints can be given in any order
+    ///
+    pub fn find_address(platform_config: Pubkey, mint_x: Pubkey, mint_y: Pubkey) -> (Pubkey, u8) {
+        let (mint_a, mint_b) = Pool::sort_mints(mint_x, mint_y);
+        Pubkey::find_program_address(
+            &[
+                "pool".as_bytes(),
+                &platform_config.to_bytes(),
+                &mint_a.to_bytes(),
+                &mint_b.to_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+
+    ///
+    /// Shard of the swap counter the pool counts its swaps in
+    ///
+    pub fn swap_counter_shard(&self) -> u8 {
+        (self.id % SWAP_COUNTER_SHARDS) as u8
+    }
+
     ///
     /// The pool is active
     ///
     pub fn is_active(&self) -> bool {
-        self.state == PoolState::Initialized
+        self.state() == PoolState::Initialized
+    }
+
+    ///
+    /// The operations are paused
+    ///
+    pub fn is_paused(&self, flags: u8) -> bool {
+        self.pause_flags & flags != 0
     }
 
     ///
-    /// The pool is paused
+    /// The key can pause the pool
+    /// Guardians of the pool or of its platform config can only pause
     ///
-    pub fn is_paused(&self) -> bool {
-        self.state == PoolState::Paused
+    pub fn can_pause(&self, key: Pubkey, platform_guardian: Pubkey) -> bool {
+        key == self.authority || key == self.guardian || key == platform_guardian
+    }
+
+    ///
+    /// The key is the authority or holds all the roles in the optional access control
+    ///
+    pub fn is_granted(
+        &self,
+        access_control: Option<&AccessControl>,
+        key: Pubkey,
+        roles: u8,
+    ) -> bool {
+        key == self.authority || access_control.is_some_and(|acl| acl.has_role(key, roles))
     }
 
     ///
@@ -124,6 +268,78 @@ impl Pool {
             .to_u64()
     }
 
+    ///
+    /// Estimate the referrer share of the lp fee
+    ///
+    pub fn calc_referral_amount(&self, fee: u64) -> Option<u64> {
+        fee.to_u128()?
+            .checked_mul(self.referral_fee.to_u128()?)?
+            .checked_div(PRECISION_U128)?
+            .to_u64()
+    }
+
+    ///
+    /// Quote the ask amount of a constant product swap
+    /// The reserves exclude the lp fees accrued in the treasuries
+    ///
+    pub fn calc_ask_amount(
+        &self,
+        bid_amount_after_fee_and_tax: u64,
+        bid_treasury_amount: u64,
+        ask_treasury_amount: u64,
+    ) -> Option<u64> {
+        // Current pool reserves
+        let (bid_reserve, ask_reserve) = self.vault_amount_without_fee(
+            bid_treasury_amount.to_u128()?,
+            ask_treasury_amount.to_u128()?,
+        );
+        // Current pool liquidity aka. the product constant
+        let liquidity = bid_reserve.checked_mul(ask_reserve)?;
+        // Next pool reserves
+        let next_bid_reserve = bid_amount_after_fee_and_tax
+            .to_u128()?
+            .checked_add(bid_reserve)?;
+        let next_ask_reserve = liquidity.checked_div(next_bid_reserve)?;
+        ask_reserve.checked_sub(next_ask_reserve)?.to_u64()
+    }
+
+    ///
+    /// Accrue the lp fee of a swap in the bid mint
+    ///
+    pub fn accrue_lp_fee(&mut self, a_to_b: bool, fee: u64) -> Option<()> {
+        if a_to_b {
+            self.lp_fees_mint_a = self.lp_fees_mint_a.checked_add(fee)?;
+        } else {
+            self.lp_fees_mint_b = self.lp_fees_mint_b.checked_add(fee)?;
+        }
+        Some(())
+    }
+
+    ///
+    /// Pool reserves of A and B, excluding the lp fees accrued in the treasuries
+    ///
+    pub fn calc_reserves(&self, treasury_a: u64, treasury_b: u64) -> Option<(u64, u64)> {
+        Some((
+            treasury_a.checked_sub(self.lp_fees_mint_a)?,
+            treasury_b.checked_sub(self.lp_fees_mint_b)?,
+        ))
+    }
+
+    ///
+    /// Pool reserves of A and B from the bid and ask treasuries of a swap
+    ///
+    pub fn calc_swap_reserves(
+        &self,
+        a_to_b: bool,
+        bid_treasury_amount: u64,
+        ask_treasury_amount: u64,
+    ) -> Option<(u64, u64)> {
+        if a_to_b {
+            return self.calc_reserves(bid_treasury_amount, ask_treasury_amount);
+        }
+        self.calc_reserves(ask_treasury_amount, bid_treasury_amount)
+    }
+
     pub fn vault_amount_without_fee(&self, vault_0: u128, vault_1: u128) -> (u128, u128) {
         (
             vault_0
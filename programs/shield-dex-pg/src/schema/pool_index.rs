@@ -0,0 +1,89 @@
+use crate::{constants::*, errors::ErrorCode, utils::realloc_account};
+use anchor_lang::prelude::*;
+
+///
+/// Pool index struct
+/// Lists the pools trading a mint, in pages of at most `MAX_POOLS_PER_INDEX_PAGE` pools.
+/// The account grows and shrinks with the list.
+///
+#[account]
+pub struct PoolIndex {
+    pub mint: Pubkey,
+    pub page: u32,
+    pub pools: Vec<Pubkey>,
+}
+
+impl PoolIndex {
+    pub const BASE_LEN: usize = ACCOUNT_DISCRIMINATOR + PUBKEY_SIZE + U32_SIZE + VECTOR_SIZE;
+
+    ///
+    /// Account size for a number of pools
+    ///
+    pub fn space(pools: usize) -> usize {
+        PoolIndex::BASE_LEN + pools * PUBKEY_SIZE
+    }
+
+    ///
+    /// The page cannot take more pools
+    ///
+    pub fn is_full(&self) -> bool {
+        self.pools.len() >= MAX_POOLS_PER_INDEX_PAGE
+    }
+
+    ///
+    /// Pages fill up in order, a page past the first only opens once the previous one is full
+    ///
+    pub fn check_page(page: u32, previous: Option<&Account<'_, PoolIndex>>) -> Result<()> {
+        if page == 0 {
+            return Ok(());
+        }
+        match previous {
+            Some(previous) if previous.is_full() => Ok(()),
+            _ => err!(ErrorCode::InvalidIndexPage),
+        }
+    }
+
+    ///
+    /// Append a pool to the page, growing the account by one entry
+    ///
+    pub fn push<'info>(
+        pool_index: &mut Account<'info, PoolIndex>,
+        pool: Pubkey,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        if pool_index.is_full() {
+            return err!(ErrorCode::PoolIndexFull);
+        }
+        realloc_account(
+            &pool_index.to_account_info(),
+            payer,
+            system_program,
+            PoolIndex::space(pool_index.pools.len() + 1),
+        )?;
+        pool_index.pools.push(pool);
+        Ok(())
+    }
+
+    ///
+    /// Drop a pool from the page, shrinking the account and refunding the rent
+    ///
+    pub fn remove<'info>(
+        pool_index: &mut Account<'info, PoolIndex>,
+        pool: Pubkey,
+        payer: &AccountInfo<'info>,
+        system_program: &AccountInfo<'info>,
+    ) -> Result<()> {
+        if let Some(position) = pool_index.pools.iter().position(|key| *key == pool) {
+            pool_index.pools.swap_remove(position);
+            realloc_account(
+                &pool_index.to_account_info(),
+                payer,
+                system_program,
+                PoolIndex::space(pool_index.pools.len()),
+            )?;
+            return Ok(());
+        }
+        err!(ErrorCode::PoolNotIndexed)
+    }
+}
@@ -0,0 +1,35 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+///
+/// Program state struct
+/// Singleton holding the admin and the program-wide counters
+/// Every admin instruction is gated on `admin`, rotated by `update_admin`
+/// Platform configs are seeded by their index, so clients can enumerate them
+/// by deriving the addresses from 0 to `total_configs`
+/// Migrated baseline configs are not counted, they keep their addresses
+/// under `LEGACY_PLATFORM_CONFIG_INDEX`
+/// A global write would serialize every swap of every pool, so the swaps
+/// are counted in `SwapCounter` shards seeded from 0 to `SWAP_COUNTER_SHARDS`
+///
+#[account]
+pub struct ProgramState {
+    pub bump: u8,
+    pub admin: Pubkey,
+    pub version: u16,
+    pub total_pools: u64,
+    pub total_configs: u16,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl ProgramState {
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR
+        + U8_SIZE
+        + PUBKEY_SIZE
+        + U16_SIZE
+        + U64_SIZE
+        + U16_SIZE
+        + I64_SIZE
+        + I64_SIZE;
+}
@@ -1,16 +1,220 @@
 use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::schema::{platform_config::PlatformConfig, pool::Pool};
 use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
 
 ///
 /// Referrer struct
+/// The binding is scoped to `pool`, or global when `pool` is the default key
+/// `parent` is the binding of `owner` itself in the same scope, linking the upper referral tiers
+/// An unbound referrer has the default key as `owner`
+/// New fields are carved out of `reserved`
 ///
 #[account]
 pub struct Referrer {
     pub owner: Pubkey,
     pub referee: Pubkey,
     pub pool: Pubkey,
+    pub version: u8,
+    pub parent: Pubkey,
+    pub bound_at: i64,
+    pub reserved: [u8; 24],
 }
 
 impl Referrer {
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + PUBKEY_SIZE
+        + I64_SIZE
+        + (RESERVED_SIZE - PUBKEY_SIZE - I64_SIZE);
+
+    ///
+    /// Load the binding of an upper tier given as a remaining account
+    /// Return none when the binding was closed, ending the chain
+    ///
+    pub fn load_upline(info: &AccountInfo, address: Pubkey) -> Result<Option<Referrer>> {
+        if info.key() != address {
+            return err!(ErrorCode::InvalidReferer);
+        }
+        if info.data_is_empty() {
+            return Ok(None);
+        }
+        if info.owner != &crate::ID {
+            return err!(anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+        }
+        Ok(Some(Referrer::try_deserialize(
+            &mut &info.try_borrow_data()?[..],
+        )?))
+    }
+
+    ///
+    /// Load the binding of a referrer in the scope of a new binding, given as `parent`
+    /// Return none when the referrer has no binding there, binding at the top tier
+    ///
+    pub fn load_parent(info: &AccountInfo) -> Result<Option<Referrer>> {
+        Ok(Referrer::load_upline(info, info.key())?.filter(|parent| parent.is_bound()))
+    }
+
+    ///
+    /// The binding has a referrer
+    ///
+    pub fn is_bound(&self) -> bool {
+        self.owner != Pubkey::default()
+    }
+
+    ///
+    /// The config of the pool scoping the binding, given the pool account
+    /// Return none for a global binding or once the pool is closed
+    ///
+    pub fn pool_config(&self, pool: Option<&AccountInfo>) -> Result<Option<Pubkey>> {
+        let Some(pool) = pool else {
+            if self.is_global() {
+                return Ok(None);
+            }
+            return err!(ErrorCode::UnmatchPool);
+        };
+        if pool.data_is_empty() {
+            return Ok(None);
+        }
+        if pool.owner != &crate::ID {
+            return err!(anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+        }
+        let data = pool.try_borrow_data()?;
+        if data.get(..ACCOUNT_DISCRIMINATOR) != Some(&Pool::DISCRIMINATOR[..]) {
+            return err!(anchor_lang::error::ErrorCode::AccountDiscriminatorMismatch);
+        }
+        // Baseline pools are shorter until migrated
+        let pool = data
+            .get(ACCOUNT_DISCRIMINATOR..Pool::LEN)
+            .ok_or(anchor_lang::error::ErrorCode::AccountDidNotDeserialize)?;
+        Ok(Some(bytemuck::from_bytes::<Pool>(pool).tax))
+    }
+
+    ///
+    /// The config holding the cooldown of the binding, given the config of its pool
+    /// Global bindings and bindings of closed pools follow the first config
+    ///
+    pub fn is_cooldown_config(
+        &self,
+        pool_config: Option<Pubkey>,
+        platform_config: &Account<PlatformConfig>,
+    ) -> bool {
+        match pool_config {
+            Some(pool_config) => pool_config == platform_config.key(),
+            None => platform_config.index == 0,
+        }
+    }
+
+    ///
+    /// The cooldown since the last binding has elapsed
+    ///
+    pub fn is_cooled_down(&self, cooldown: i64, now: i64) -> bool {
+        self.bound_at
+            .checked_add(cooldown)
+            .is_some_and(|earliest| now >= earliest)
+    }
+
+    ///
+    /// The referee must not be the referrer or one of its uplines within the paid tiers,
+    /// given as the parent binding then the bindings above it
+    ///
+    pub fn check_cycle(
+        owner: Pubkey,
+        referee: Pubkey,
+        parent: Option<&Referrer>,
+        uplines: &[AccountInfo],
+    ) -> Result<()> {
+        if owner == referee {
+            return err!(ErrorCode::ReferralCycle);
+        }
+        let Some(parent) = parent else {
+            return Ok(());
+        };
+        let mut uplines = uplines.iter();
+        let mut upline = parent.clone();
+        let mut tier = 1;
+        loop {
+            if upline.owner == referee {
+                return err!(ErrorCode::ReferralCycle);
+            }
+            tier += 1;
+            if tier >= MAX_REFERRAL_TIERS || !upline.has_parent() {
+                return Ok(());
+            }
+            let info = uplines.next().ok_or(ErrorCode::MissingReferralAccount)?;
+            match Referrer::load_upline(info, upline.parent)? {
+                Some(next) if next.is_bound() => upline = next,
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    ///
+    /// The binding links to the binding of its referrer
+    ///
+    pub fn has_parent(&self) -> bool {
+        self.parent != Pubkey::default()
+    }
+
+    ///
+    /// The scope seed of a binding, the default key for global bindings
+    ///
+    pub fn scope(pool: Option<Pubkey>) -> Pubkey {
+        pool.unwrap_or_default()
+    }
+
+    ///
+    /// The binding applies to every pool
+    ///
+    pub fn is_global(&self) -> bool {
+        self.pool == Pubkey::default()
+    }
+
+    ///
+    /// Find the referrer account of a referee in a scope
+    ///
+    pub fn find_address(pool: Option<Pubkey>, referee: Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(
+            &[
+                "referrer".as_bytes(),
+                &Referrer::scope(pool).to_bytes(),
+                &referee.to_bytes(),
+            ],
+            &crate::ID,
+        )
+    }
+}
+
+///
+/// Referrer layout of the baseline program, seeded by the referee only
+/// Baseline bindings never set their pool, so they are all global
+///
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct ReferrerV0 {
+    pub owner: Pubkey,
+    pub referee: Pubkey,
+    pub pool: Pubkey,
+}
+
+impl ReferrerV0 {
     pub const LEN: usize = ACCOUNT_DISCRIMINATOR + PUBKEY_SIZE + PUBKEY_SIZE + PUBKEY_SIZE;
+
+    ///
+    /// Convert to a global binding of the current layout, without parent nor binding time
+    ///
+    pub fn migrate(self) -> Referrer {
+        Referrer {
+            owner: self.owner,
+            referee: self.referee,
+            pool: Pubkey::default(),
+            version: REFERRER_VERSION,
+            parent: Pubkey::default(),
+            bound_at: 0,
+            reserved: [0; 24],
+        }
+    }
 }
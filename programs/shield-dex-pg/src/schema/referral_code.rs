@@ -0,0 +1,46 @@
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use anchor_lang::prelude::*;
+
+///
+/// Referral code struct
+/// Seeded by the code, so a code is owned by a single referrer
+///
+#[account]
+pub struct ReferralCode {
+    pub owner: Pubkey,
+    pub code: String,
+    pub bump: u8,
+    pub created_at: i64,
+}
+
+impl ReferralCode {
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + VECTOR_SIZE
+        + MAX_REFERRAL_CODE_LEN
+        + U8_SIZE
+        + I64_SIZE;
+
+    ///
+    /// Codes are made of uppercase letters and digits, e.g. "ALICE10"
+    /// A single case keeps look-alike codes from being registered twice
+    ///
+    pub fn is_valid_code(code: &str) -> bool {
+        (MIN_REFERRAL_CODE_LEN..=MAX_REFERRAL_CODE_LEN).contains(&code.len())
+            && code
+                .bytes()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit())
+    }
+
+    ///
+    /// The code as the seed of its account, validated first
+    /// A code past the seed length would abort the address derivation instead of failing
+    ///
+    pub fn seed(code: &str) -> Result<&[u8]> {
+        if !ReferralCode::is_valid_code(code) {
+            return err!(ErrorCode::InvalidReferralCode);
+        }
+        Ok(code.as_bytes())
+    }
+}
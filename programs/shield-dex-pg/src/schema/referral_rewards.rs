@@ -0,0 +1,56 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+///
+/// Referral rewards struct
+/// Balance of a referrer in a mint, credited by swaps and paid out of the referral vault
+///
+#[account]
+pub struct ReferralRewards {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub accrued: u64,
+    pub total_claimed: u64,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl ReferralRewards {
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR
+        + PUBKEY_SIZE
+        + PUBKEY_SIZE
+        + U8_SIZE
+        + U64_SIZE
+        + U64_SIZE
+        + I64_SIZE
+        + I64_SIZE;
+
+    ///
+    /// The rewards were set up by an earlier swap
+    /// False on a zeroed account that `init_if_needed` has just created for the direct referrer
+    ///
+    pub fn is_initialized(&self) -> bool {
+        self.owner != Pubkey::default()
+    }
+
+    ///
+    /// Credit a referral fee to the balance
+    ///
+    pub fn accrue(&mut self, amount: u64, now: i64) -> Option<()> {
+        self.accrued = self.accrued.checked_add(amount)?;
+        self.updated_at = now;
+        Some(())
+    }
+
+    ///
+    /// Empty the balance and return the claimed amount
+    ///
+    pub fn claim(&mut self, now: i64) -> Option<u64> {
+        let amount = self.accrued;
+        self.total_claimed = self.total_claimed.checked_add(amount)?;
+        self.accrued = 0;
+        self.updated_at = now;
+        Some(amount)
+    }
+}
@@ -0,0 +1,109 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+///
+/// Referred volume and rewards of a referrer in a mint
+///
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct MintStats {
+    pub mint: Pubkey,
+    pub volume: u64,
+    pub rewards: u64,
+}
+
+impl MintStats {
+    pub const LEN: usize = PUBKEY_SIZE + U64_SIZE + U64_SIZE;
+}
+
+///
+/// Referral stats struct
+/// `total_referees` counts the referees currently bound to the referrer
+/// The volume is the bid volume of the direct referees, the rewards cover every tier
+/// Mints past `MAX_REFERRAL_STATS_MINTS` are not tracked
+/// The account grows with the list of mints
+///
+#[account]
+pub struct ReferralStats {
+    pub owner: Pubkey,
+    pub bump: u8,
+    pub total_referees: u64,
+    pub updated_at: i64,
+    pub mints: Vec<MintStats>,
+}
+
+impl ReferralStats {
+    pub const BASE_LEN: usize =
+        ACCOUNT_DISCRIMINATOR + PUBKEY_SIZE + U8_SIZE + U64_SIZE + I64_SIZE + VECTOR_SIZE;
+
+    ///
+    /// Account size for a number of mints
+    ///
+    pub fn space(mints: usize) -> usize {
+        ReferralStats::BASE_LEN + mints * MintStats::LEN
+    }
+
+    ///
+    /// The owner is already set
+    /// Binding a referee creates the stats through `init_if_needed`, swaps through `create_pda_account`,
+    /// so the owner stays default until one of them fills it in
+    ///
+    pub fn is_initialized(&self) -> bool {
+        self.owner != Pubkey::default()
+    }
+
+    ///
+    /// Count a new referee
+    ///
+    pub fn add_referee(&mut self, now: i64) -> Option<()> {
+        self.total_referees = self.total_referees.checked_add(1)?;
+        self.updated_at = now;
+        Some(())
+    }
+
+    ///
+    /// Uncount a referee leaving the referrer
+    /// Bindings migrated from the baseline were never counted, so the count saturates at zero
+    ///
+    pub fn remove_referee(&mut self, now: i64) {
+        self.total_referees = self.total_referees.saturating_sub(1);
+        self.updated_at = now;
+    }
+
+    ///
+    /// Uncount a referee in the stats of its previous referrer, skipped when they do not exist
+    ///
+    pub fn remove_referee_from(info: &AccountInfo, now: i64) -> Result<()> {
+        if info.data_is_empty() {
+            return Ok(());
+        }
+        if info.owner != &crate::ID {
+            return err!(anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+        }
+        let mut stats = ReferralStats::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        stats.remove_referee(now);
+        stats.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])?;
+        Ok(())
+    }
+
+    ///
+    /// Add the volume and the rewards of a swap in a mint
+    /// Return whether a mint was added, so that the account has to grow by one entry
+    ///
+    pub fn record(&mut self, mint: Pubkey, volume: u64, rewards: u64, now: i64) -> Option<bool> {
+        self.updated_at = now;
+        if let Some(stats) = self.mints.iter_mut().find(|stats| stats.mint == mint) {
+            stats.volume = stats.volume.checked_add(volume)?;
+            stats.rewards = stats.rewards.checked_add(rewards)?;
+            return Some(false);
+        }
+        if self.mints.len() >= MAX_REFERRAL_STATS_MINTS {
+            return Some(false);
+        }
+        self.mints.push(MintStats {
+            mint,
+            volume,
+            rewards,
+        });
+        Some(true)
+    }
+}
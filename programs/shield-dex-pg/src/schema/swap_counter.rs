@@ -0,0 +1,28 @@
+use crate::constants::*;
+use anchor_lang::prelude::*;
+
+///
+/// Swap counter struct
+/// One of the `SWAP_COUNTER_SHARDS` shards counting the swaps of the protocol, picked by the pool id
+/// The protocol swap count is the sum of the shards, volumes are left to the swap events
+///
+#[account]
+pub struct SwapCounter {
+    pub shard: u8,
+    pub bump: u8,
+    pub total_swaps: u64,
+    pub last_swap_at: i64,
+}
+
+impl SwapCounter {
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR + U8_SIZE + U8_SIZE + U64_SIZE + I64_SIZE;
+
+    ///
+    /// Count a swap in the shard
+    ///
+    pub fn record_swap(&mut self, now: i64) -> Option<()> {
+        self.total_swaps = self.total_swaps.checked_add(1)?;
+        self.last_swap_at = now;
+        Some(())
+    }
+}
@@ -0,0 +1,44 @@
+use crate::constants::*;
+use anchor_lang::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+///
+/// Token status
+///
+#[repr(u8)]
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum TokenStatus {
+    Unlisted,
+    Allowed,
+    Denied,
+}
+
+///
+/// Token registry struct
+///
+#[account]
+pub struct TokenRegistry {
+    pub mint: Pubkey,
+    pub status: TokenStatus,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl TokenRegistry {
+    pub const LEN: usize = ACCOUNT_DISCRIMINATOR + PUBKEY_SIZE + U8_SIZE + I64_SIZE + I64_SIZE;
+
+    ///
+    /// Read the status of a registry PDA
+    /// A registry that was never created means the mint is unlisted
+    ///
+    pub fn status_of(registry: &AccountInfo) -> Result<TokenStatus> {
+        if registry.data_is_empty() {
+            return Ok(TokenStatus::Unlisted);
+        }
+        if registry.owner != &crate::ID {
+            return err!(ErrorCode::AccountOwnedByWrongProgram);
+        }
+        let registry = TokenRegistry::try_deserialize(&mut &registry.try_borrow_data()?[..])?;
+        Ok(registry.status)
+    }
+}
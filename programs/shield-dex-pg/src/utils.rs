@@ -1,4 +1,21 @@
+use crate::{constants::*, errors::ErrorCode};
+use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::system_program;
+use anchor_spl::{
+    token,
+    token_2022::{
+        self,
+        spl_token_2022::{
+            self,
+            extension::{
+                metadata_pointer, BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+            },
+        },
+    },
+};
+use spl_token_metadata_interface::state::TokenMetadata;
 
 pub fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
     let preimage = format!("{}:{}", namespace, name);
@@ -6,3 +23,319 @@ pub fn discriminator(namespace: &str, name: &str) -> [u8; 8] {
     sighash.copy_from_slice(&solana_program::hash::hash(preimage.as_bytes()).to_bytes()[..8]);
     sighash
 }
+
+///
+/// Token-2022 extensions that let a third party freeze, seize, tax or block
+/// the tokens held by the pool treasuries
+///
+pub const DANGEROUS_EXTENSIONS: [ExtensionType; 6] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::MintCloseAuthority,
+    ExtensionType::DefaultAccountState,
+    ExtensionType::NonTransferable,
+    ExtensionType::PermanentDelegate,
+    ExtensionType::TransferHook,
+];
+
+///
+/// Inspect a mint of the SPL Token or Token-2022 program and return its risk flags
+///
+pub fn mint_risk_flags(mint: &AccountInfo) -> Result<u8> {
+    let data = mint.try_borrow_data()?;
+    let mut flags = 0;
+
+    let (mint_authority, freeze_authority) = if mint.owner == &token_2022::ID {
+        let state = StateWithExtensions::<token_2022::spl_token_2022::state::Mint>::unpack(&data)?;
+        if state
+            .get_extension_types()?
+            .iter()
+            .any(|extension| DANGEROUS_EXTENSIONS.contains(extension))
+        {
+            flags |= RISK_DANGEROUS_EXTENSIONS;
+        }
+        (state.base.mint_authority, state.base.freeze_authority)
+    } else if mint.owner == &token::ID {
+        let state = token::spl_token::state::Mint::unpack(&data)?;
+        (state.mint_authority, state.freeze_authority)
+    } else {
+        return err!(anchor_lang::error::ErrorCode::AccountOwnedByWrongProgram);
+    };
+
+    if freeze_authority.is_some() {
+        flags |= RISK_FREEZE_AUTHORITY;
+    }
+    if mint_authority.is_some() {
+        flags |= RISK_MINT_AUTHORITY;
+    }
+    Ok(flags)
+}
+
+///
+/// Resize a program account, topping up its rent from the payer when it grows
+/// and refunding the excess rent to the payer when it shrinks
+/// The grown bytes are zeroed
+///
+pub fn realloc_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    new_len: usize,
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(new_len);
+    let lamports = account.lamports();
+
+    if rent > lamports {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            rent - lamports,
+        )?;
+    } else if lamports > rent {
+        **account.try_borrow_mut_lamports()? -= lamports - rent;
+        **payer.try_borrow_mut_lamports()? += lamports - rent;
+    }
+    account.realloc(new_len, true)?;
+
+    Ok(())
+}
+
+///
+/// Close a program account, as `close` does for declared accounts
+/// The rent goes to the destination and the account returns to the system program
+///
+pub fn close_account<'info>(
+    account: &AccountInfo<'info>,
+    destination: &AccountInfo<'info>,
+) -> Result<()> {
+    let lamports = account.lamports();
+    **account.try_borrow_mut_lamports()? = 0;
+    **destination.try_borrow_mut_lamports()? = destination
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ErrorCode::Overflow)?;
+    account.assign(&system_program::ID);
+    account.realloc(0, false)?;
+
+    Ok(())
+}
+
+///
+/// Create a program account at a PDA, as `init` does for declared accounts
+/// Accounts prefunded with lamports are topped up, allocated and assigned instead
+///
+pub fn create_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    create_owned_pda_account(
+        account,
+        payer,
+        system_program,
+        rent,
+        space,
+        &crate::ID,
+        seeds,
+    )
+}
+
+///
+/// Create an account at a PDA, funded with the given lamports and owned by the given program
+/// Anyone can send lamports to a PDA, so prefunded accounts are topped up,
+/// allocated and assigned instead of failing to be created
+///
+pub fn create_owned_pda_account<'info>(
+    account: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    lamports: u64,
+    space: usize,
+    owner: &Pubkey,
+    seeds: &[&[u8]],
+) -> Result<()> {
+    let balance = account.lamports();
+
+    if balance == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+                &[seeds],
+            ),
+            lamports,
+            space as u64,
+            owner,
+        );
+    }
+    if lamports > balance {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            lamports - balance,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: account.clone(),
+            },
+            &[seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: account.clone(),
+            },
+            &[seeds],
+        ),
+        owner,
+    )
+}
+
+///
+/// LP mint name derived from the symbols of the pool's mints, e.g. "SOL-USDC LP"
+///
+pub fn lp_mint_name(symbol_a: &str, symbol_b: &str) -> String {
+    format!("{}-{} LP", symbol_a, symbol_b)
+}
+
+///
+/// Symbol of a mint, read from its Token-2022 metadata or its Metaplex metadata account
+/// Mints without a symbol go by the first characters of their address, e.g. "So11"
+///
+pub fn mint_symbol(mint: &AccountInfo, metadata: Option<&AccountInfo>) -> Result<String> {
+    let mut symbol = None;
+    if mint.owner == &token_2022::ID {
+        let data = mint.try_borrow_data()?;
+        let state = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&data)?;
+        symbol = state
+            .get_variable_len_extension::<TokenMetadata>()
+            .ok()
+            .map(|metadata| metadata.symbol);
+    }
+    if let (None, Some(metadata)) = (&symbol, metadata) {
+        if metadata.owner == &crate::metaplex::ID {
+            // The key, the update authority and the mint precede the name and the symbol
+            let data = metadata.try_borrow_data()?;
+            let mut fields = data
+                .get(1 + PUBKEY_SIZE + PUBKEY_SIZE..)
+                .unwrap_or_default();
+            symbol = String::deserialize(&mut fields)
+                .and_then(|_name| String::deserialize(&mut fields))
+                .ok();
+        }
+    }
+
+    // Metaplex pads the symbols with null characters
+    let symbol: String = symbol
+        .unwrap_or_default()
+        .trim_matches(char::from(0))
+        .trim()
+        .chars()
+        .take(LP_MINT_NAME_SYMBOL_CHARS)
+        .collect();
+    if symbol.is_empty() {
+        return Ok(mint.key().to_string()[..LP_MINT_NAME_MINT_CHARS].to_string());
+    }
+    Ok(symbol)
+}
+
+///
+/// Create a Token-2022 LP mint that carries its own metadata
+/// The metadata pointer targets the mint itself, and the escrow is
+/// the mint authority, the metadata update authority and the close authority
+/// so that closing the pool frees the LP mint address for a new pool
+///
+#[allow(clippy::too_many_arguments)]
+pub fn create_lp_mint<'info>(
+    lp_mint: &AccountInfo<'info>,
+    escrow: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    token_program: &AccountInfo<'info>,
+    lp_mint_seeds: &[&[u8]],
+    escrow_seeds: &[&[u8]],
+    metadata: TokenMetadata,
+) -> Result<()> {
+    let space = ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+        ExtensionType::MetadataPointer,
+        ExtensionType::MintCloseAuthority,
+    ])?;
+    // Token-2022 grows the mint for the metadata, so the rent is paid upfront
+    let lamports = Rent::get()?.minimum_balance(space + metadata.tlv_size_of()?);
+
+    // The LP mint address is known ahead of the pool, so it may be prefunded
+    create_owned_pda_account(
+        lp_mint,
+        payer,
+        system_program,
+        lamports,
+        space,
+        &token_2022::ID,
+        lp_mint_seeds,
+    )?;
+    solana_program::program::invoke(
+        &metadata_pointer::instruction::initialize(
+            &token_2022::ID,
+            &lp_mint.key(),
+            Some(escrow.key()),
+            Some(lp_mint.key()),
+        )?,
+        std::slice::from_ref(lp_mint),
+    )?;
+    solana_program::program::invoke(
+        &spl_token_2022::instruction::initialize_mint_close_authority(
+            &token_2022::ID,
+            &lp_mint.key(),
+            Some(&escrow.key()),
+        )?,
+        std::slice::from_ref(lp_mint),
+    )?;
+    token_2022::initialize_mint2(
+        CpiContext::new(
+            token_program.clone(),
+            token_2022::InitializeMint2 {
+                mint: lp_mint.clone(),
+            },
+        ),
+        LP_MINT_DECIMALS,
+        &escrow.key(),
+        Some(&escrow.key()),
+    )?;
+    solana_program::program::invoke_signed(
+        &spl_token_metadata_interface::instruction::initialize(
+            &token_2022::ID,
+            &lp_mint.key(),
+            &escrow.key(),
+            &lp_mint.key(),
+            &escrow.key(),
+            metadata.name,
+            metadata.symbol,
+            metadata.uri,
+        ),
+        &[lp_mint.clone(), escrow.clone()],
+        &[escrow_seeds],
+    )?;
+
+    Ok(())
+}
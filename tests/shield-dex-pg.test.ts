@@ -1,10 +1,41 @@
 import * as anchor from "@coral-xyz/anchor";
-import { BN, IdlTypes, Program, utils, web3 } from "@coral-xyz/anchor";
+import { BN, Program, utils, web3 } from "@coral-xyz/anchor";
 import { ShieldDexPg } from "../target/types/shield_dex_pg";
 import { expect } from "chai";
+import { mkdirSync, writeFileSync } from "fs";
 
-import Texture, { BILLION, HUNDRED, ONE, TEN, ZERO } from "./texture";
-import { getLamports, getReferrerAddress } from "./utils";
+import Texture, { BILLION, HUNDRED, ONE, TEN, THOUSAND, ZERO } from "./texture";
+import {
+  FIXTURES,
+  getAccessControlAddress,
+  getAssociatedTokenAddress,
+  PARAM_CHANGE,
+  getComputeUnits,
+  getCurrentTimestamp,
+  getEvents,
+  getLamports,
+  getLpTokenAccount,
+  getPendingChangeAddress,
+  getPlatformConfigAddress,
+  getPoolAddress,
+  getPoolIndexAddress,
+  getProgramStateAddress,
+  getReferralCodeAddress,
+  getReferralRewardsAddress,
+  getReferralStatsAddress,
+  getReferralVaultAddress,
+  getReferrerAddress,
+  getSwapCounterAddress,
+  getTokenBalance,
+  getTokenRegistryAddress,
+  initializeToken2022Mint,
+  initializeToken2022MintWithMetadata,
+  initializeToken2022MintWithTransferFee,
+  sortMints,
+  TOKEN_2022_PROGRAM_ID,
+  transferLamports,
+  waitUntil,
+} from "./utils";
 import { publicKey } from "@coral-xyz/anchor/dist/cjs/utils";
 
 describe("shield-dex-pg", () => {
@@ -15,28 +46,76 @@ describe("shield-dex-pg", () => {
   const program = anchor.workspace.ShieldDexPg as Program<ShieldDexPg>;
   const texture = new Texture(provider);
 
-  const STATE: Record<string, IdlTypes<ShieldDexPg>["PoolState"]> = {
-    Uninitialized: { uninitialized: {} },
-    Initialized: { initialized: {} },
-    Paused: { paused: {} },
-    Canceled: { canceled: {} },
+  // Index of the migrated baseline configs, which are not seeded by their index
+  const LEGACY_PLATFORM_CONFIG_INDEX = 65535;
+
+  // Minimum timelock delay of the platform config, in seconds
+  const TIMELOCK_DELAY = 60;
+  // Longest timelock delay, bounding the eta of the changes
+  const MAX_TIMELOCK_DELAY = 2592000;
+
+  // The zero-copy pool stores its state as a number
+  const STATE = {
+    Uninitialized: 0,
+    Initialized: 1,
+    Paused: 2,
+    Canceled: 3,
+  };
+
+  const PAUSE = {
+    Swap: 1 << 0,
+    Deposit: 1 << 1,
+    Withdraw: 1 << 2,
+    FeeClaim: 1 << 3,
+  };
+
+  const ROLE = {
+    FeeManager: 1 << 0,
+    Pauser: 1 << 1,
+    FeeCollector: 1 << 2,
+    ReferralManager: 1 << 3,
+    PoolManager: 1 << 4,
+  };
+
+  const RISK = {
+    FreezeAuthority: 1 << 0,
+    MintAuthority: 1 << 1,
+    DangerousExtensions: 1 << 2,
   };
 
+  const [programState] = getProgramStateAddress(program.programId);
+  const [referralVault] = getReferralVaultAddress(program.programId);
+  const [platformConfig] = getPlatformConfigAddress(0, program.programId);
+  const [poolAB] = getPoolAddress(
+    platformConfig,
+    texture.A.mint.publicKey,
+    texture.B.mint.publicKey,
+    program.programId
+  );
+  // The pool of (A,B) is the first pool, counted in the first shard of the swap counter
+  const [swapCounterAB] = getSwapCounterAddress(ZERO, program.programId);
+  const [poolBC] = getPoolAddress(
+    platformConfig,
+    texture.B.mint.publicKey,
+    texture.C.mint.publicKey,
+    program.programId
+  );
+
   const [escrowAB] = web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("escrow"), texture.poolAB.publicKey.toBuffer()],
+    [Buffer.from("escrow"), poolAB.toBuffer()],
     program.programId
   );
   const [lpMintAB] = web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("lp_mint"), texture.poolAB.publicKey.toBuffer()],
+    [Buffer.from("lp_mint"), poolAB.toBuffer()],
     program.programId
   );
 
   const [escrowBC] = web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("escrow"), texture.poolBC.publicKey.toBuffer()],
+    [Buffer.from("escrow"), poolBC.toBuffer()],
     program.programId
   );
   const [lpMintBC] = web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("lp_mint"), texture.poolBC.publicKey.toBuffer()],
+    [Buffer.from("lp_mint"), poolBC.toBuffer()],
     program.programId
   );
 
@@ -48,6 +127,65 @@ describe("shield-dex-pg", () => {
     await texture.init();
   });
 
+  /**
+   * Compute units consumed per instruction, written to target/cu-benchmarks.json
+   */
+  const computeUnits: Record<string, number> = {};
+  const benchmark = async (name: string, txId: string) => {
+    computeUnits[name] = await getComputeUnits(txId, provider);
+  };
+
+  /**
+   * Accounts of a pool of the platform config, listed on the first index pages
+   */
+  const getPoolAccounts = (mintX: web3.PublicKey, mintY: web3.PublicKey) => {
+    const [mintA, mintB] = sortMints(mintX, mintY);
+    const [pool] = getPoolAddress(
+      platformConfig,
+      mintA,
+      mintB,
+      program.programId
+    );
+    const [escrow] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), pool.toBuffer()],
+      program.programId
+    );
+    const [lpMint] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), pool.toBuffer()],
+      program.programId
+    );
+    return {
+      pool,
+      mintA,
+      treasuryA: utils.token.associatedAddress({ owner: escrow, mint: mintA }),
+      mintB,
+      treasuryB: utils.token.associatedAddress({ owner: escrow, mint: mintB }),
+      lpMint,
+      escrow,
+      accessControl: getAccessControlAddress(pool, program.programId)[0],
+      poolIndexA: getPoolIndexAddress(mintA, 0, program.programId)[0],
+      poolIndexB: getPoolIndexAddress(mintB, 0, program.programId)[0],
+      pendingLpFeeChange: getPendingChangeAddress(
+        pool,
+        PARAM_CHANGE.LpFee,
+        program.programId
+      )[0],
+      pendingReferralFeeChange: getPendingChangeAddress(
+        pool,
+        PARAM_CHANGE.ReferralFee,
+        program.programId
+      )[0],
+    };
+  };
+
+  after(() => {
+    mkdirSync("target", { recursive: true });
+    writeFileSync(
+      "target/cu-benchmarks.json",
+      JSON.stringify(computeUnits, null, 2)
+    );
+  });
+
   /**
    * Verify
    * A = 1_000_000_000 * 10^6
@@ -70,6 +208,25 @@ describe("shield-dex-pg", () => {
       .true;
   });
 
+  it("initialize program state", async () => {
+    await program.methods
+      .initializeProgramState()
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const { admin, version, totalPools, totalConfigs } =
+      await program.account.programState.fetch(programState);
+
+    expect(admin).deep.equal(provider.publicKey);
+    expect(version).equal(1);
+    expect(totalPools.eq(ZERO)).to.be.true;
+    expect(totalConfigs).equal(0);
+  });
+
   it("create platform config", async () => {
     let secretKey = Uint8Array.from([
       118, 210, 107, 166, 206, 218, 42, 167, 221, 236, 90, 208, 179, 145, 200,
@@ -77,40 +234,107 @@ describe("shield-dex-pg", () => {
       238,
     ]);
     console.log("secretKey: ", secretKey);
-    await program.methods
-      .createPlatformConfig(new BN(2500000))
+    const txId = await program.methods
+      .createPlatformConfig(
+        {
+          defaultLpFee: ZERO,
+          minLpFee: ZERO,
+          maxLpFee: BILLION,
+          tax: new BN(2500000),
+          maxReferralFee: BILLION,
+          allowedCurves: 1,
+        },
+        provider.publicKey,
+        new BN(TIMELOCK_DELAY)
+      )
       .accounts({
         owner: provider.publicKey,
-        platformConfig: texture.platformConfig.publicKey,
+        programState,
+        platformConfig,
         systemProgram: web3.SystemProgram.programId,
       })
-      .signers([texture.platformConfig])
       .rpc();
 
-    const { tax } = await program.account.platformConfig.fetch(
-      texture.platformConfig.publicKey
+    const { index, tax, feeReceiver } =
+      await program.account.platformConfig.fetch(platformConfig);
+    const { totalConfigs } = await program.account.programState.fetch(
+      programState
     );
 
     expect(tax.eq(new BN(2500000))).to.be.true;
+    expect(feeReceiver).deep.equal(provider.publicKey);
+    expect(index).equal(0);
+    expect(totalConfigs).equal(1);
+    // Emitted through a self-invocation
+    const events = await getEvents(txId, program as any);
+    const { data } = events.find(
+      ({ name }) => name === "CreatePlatformConfigEvent"
+    );
+    expect(data.platformConfig.equals(platformConfig)).to.be.true;
+    expect(data.index).equal(0);
+    expect(data.feeTier.tax.eq(new BN(2500000))).to.be.true;
+    expect(data.feeReceiver.equals(provider.publicKey)).to.be.true;
+  });
+
+  it("hand the program over to Bob and back", async () => {
+    await program.methods
+      .updateAdmin(texture.Bob.keypair.publicKey)
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+      })
+      .rpc();
+    const { admin } = await program.account.programState.fetch(programState);
+    expect(admin).deep.equal(texture.Bob.keypair.publicKey);
+
+    // The former admin lost every admin instruction
+    try {
+      await program.methods
+        .updateGuardian(provider.publicKey)
+        .accounts({
+          owner: provider.publicKey,
+          programState,
+          platformConfig,
+        })
+        .rpc();
+      expect.fail("the former admin designates the guardian");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("Unauthorized");
+    }
+
+    await program.methods
+      .updateAdmin(provider.publicKey)
+      .accounts({
+        owner: texture.Bob.keypair.publicKey,
+        programState,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+    const { admin: nextAdmin } = await program.account.programState.fetch(
+      programState
+    );
+    expect(nextAdmin).deep.equal(provider.publicKey);
   });
 
   it("initialized a pool of (A,B)", async () => {
     const platform = await program.account.platformConfig.fetch(
-      texture.platformConfig.publicKey
+      platformConfig
     );
-    await program.methods
+    const txId = await program.methods
       .initialize(
         texture.A.amount.init,
         texture.B.amount.init,
         // new BN(1000000000000),
         ZERO,
-        ONE,
-        ZERO
+        ZERO,
+        0,
+        0
       )
       .accounts({
         authority: provider.publicKey,
-        platformConfig: texture.platformConfig.publicKey,
-        pool: texture.poolAB.publicKey,
+        programState,
+        platformConfig,
+        pool: poolAB,
         mintA: texture.A.mint.publicKey,
         srcA: utils.token.associatedAddress({
           owner: provider.publicKey,
@@ -130,22 +354,46 @@ describe("shield-dex-pg", () => {
           mint: texture.B.mint.publicKey,
         }),
         lpMint: lpMintAB,
-        dstLp: utils.token.associatedAddress({
-          owner: provider.publicKey,
-          mint: lpMintAB,
-        }),
+        metadataA: null,
+        metadataB: null,
+        dstLp: getLpTokenAccount(provider.publicKey, lpMintAB),
+        feeSrc: null,
+        feeDst: null,
         escrow: escrowAB,
+        tokenRegistryA: getTokenRegistryAddress(
+          texture.A.mint.publicKey,
+          program.programId
+        )[0],
+        tokenRegistryB: getTokenRegistryAddress(
+          texture.B.mint.publicKey,
+          program.programId
+        )[0],
+        poolIndexA: getPoolIndexAddress(
+          texture.A.mint.publicKey,
+          0,
+          program.programId
+        )[0],
+        poolIndexB: getPoolIndexAddress(
+          texture.B.mint.publicKey,
+          0,
+          program.programId
+        )[0],
+        prevPoolIndexA: null,
+        prevPoolIndexB: null,
         taxman: provider.publicKey,
         tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+        token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
         rent: web3.SYSVAR_RENT_PUBKEY,
       })
-      .signers([texture.poolAB])
       .rpc();
+    await benchmark("initialize", txId);
 
-    const { authority, mintA, mintB, lpFee, tax, state } =
-      await program.account.pool.fetch(texture.poolAB.publicKey);
+    const { id, authority, mintA, mintB, lpFee, tax, state, riskFlags } =
+      await program.account.pool.fetch(poolAB);
     const { amount: a } = await texture.spl.account.account.fetch(
       utils.token.associatedAddress({
         owner: escrowAB,
@@ -158,269 +406,2878 @@ describe("shield-dex-pg", () => {
         mint: texture.B.mint.publicKey,
       })
     );
-    const { amount: lp } = await texture.spl.account.account.fetch(
-      utils.token.associatedAddress({
-        owner: provider.publicKey,
-        mint: lpMintAB,
-      })
+    const lp = await getTokenBalance(
+      getLpTokenAccount(provider.publicKey, lpMintAB),
+      provider
     );
 
     const balance = await getLamports(texture.taxman.publicKey, provider);
 
+    expect(id.eq(ZERO)).to.be.true;
     expect(authority).deep.equal(provider.publicKey);
     expect(mintA).deep.equal(texture.A.mint.publicKey);
     expect(mintB).deep.equal(texture.B.mint.publicKey);
     // expect(lpFee.eq(ZERO)).to.be.true;
     expect(state).deep.equal(STATE.Initialized);
+    // The texture mints keep both their mint and freeze authorities
+    expect(riskFlags).equal(RISK.FreezeAuthority | RISK.MintAuthority);
     // expect(a.eq(texture.A.amount.init)).to.be.true;
     // expect(b.eq(texture.B.amount.init)).to.be.true;
     // expect(lp.toString()).equal("1000000000000");
   });
 
-  it("create referrer", async () => {
-    const [referrer] = await getReferrerAddress(
-      provider.publicKey,
+  it("reject a Token-2022 mint with a dangerous extension", async () => {
+    const mint = web3.Keypair.generate();
+    await initializeToken2022Mint(6, mint, provider);
+    const [mintA, mintB] = sortMints(mint.publicKey, texture.A.mint.publicKey);
+    const tokenProgramOf = (address: web3.PublicKey) =>
+      address.equals(texture.A.mint.publicKey)
+        ? utils.token.TOKEN_PROGRAM_ID
+        : TOKEN_2022_PROGRAM_ID;
+    const [pool] = getPoolAddress(
+      platformConfig,
+      mintA,
+      mintB,
+      program.programId
+    );
+    const [escrow] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), pool.toBuffer()],
+      program.programId
+    );
+    const [lpMint] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), pool.toBuffer()],
       program.programId
     );
 
     await program.methods
-      .createReferrer(texture.Bob.keypair.publicKey)
+      .updateMintPolicy(RISK.DangerousExtensions)
+      .accounts({ owner: provider.publicKey, programState, platformConfig })
+      .rpc();
+    try {
+      await program.methods
+        .initialize(ONE, ONE, ZERO, null, 0, 0)
+        .accounts({
+          authority: provider.publicKey,
+          programState,
+          platformConfig,
+          pool,
+          mintA,
+          srcA: getAssociatedTokenAddress(
+            provider.publicKey,
+            mintA,
+            tokenProgramOf(mintA)
+          ),
+          treasuryA: getAssociatedTokenAddress(
+            escrow,
+            mintA,
+            tokenProgramOf(mintA)
+          ),
+          mintB,
+          srcB: getAssociatedTokenAddress(
+            provider.publicKey,
+            mintB,
+            tokenProgramOf(mintB)
+          ),
+          treasuryB: getAssociatedTokenAddress(
+            escrow,
+            mintB,
+            tokenProgramOf(mintB)
+          ),
+          lpMint,
+          metadataA: null,
+          metadataB: null,
+          dstLp: getLpTokenAccount(provider.publicKey, lpMint),
+          feeSrc: null,
+          feeDst: null,
+          escrow,
+          tokenRegistryA: getTokenRegistryAddress(mintA, program.programId)[0],
+          tokenRegistryB: getTokenRegistryAddress(mintB, program.programId)[0],
+          poolIndexA: getPoolIndexAddress(mintA, 0, program.programId)[0],
+          poolIndexB: getPoolIndexAddress(mintB, 0, program.programId)[0],
+          prevPoolIndexA: null,
+          prevPoolIndexB: null,
+          taxman: provider.publicKey,
+          tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: tokenProgramOf(mintA),
+          tokenProgramB: tokenProgramOf(mintB),
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+      expect.fail("a mint with a dangerous extension is pooled");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("UnsafeMint");
+    } finally {
+      await program.methods
+        .updateMintPolicy(0)
+        .accounts({ owner: provider.publicKey, programState, platformConfig })
+        .rpc();
+    }
+  });
+
+  it("name the LP mint after the symbols of the pool mints", async () => {
+    const mint = web3.Keypair.generate();
+    await initializeToken2022MintWithMetadata(
+      6,
+      mint,
+      "Shield",
+      "SHLD",
+      new BN(1_000_000),
+      provider
+    );
+    const [mintA, mintB] = sortMints(mint.publicKey, texture.A.mint.publicKey);
+    const tokenProgramOf = (address: web3.PublicKey) =>
+      address.equals(texture.A.mint.publicKey)
+        ? utils.token.TOKEN_PROGRAM_ID
+        : TOKEN_2022_PROGRAM_ID;
+    // Mints without metadata fall back to the prefix of their address
+    const symbolOf = (address: web3.PublicKey) =>
+      address.equals(mint.publicKey) ? "SHLD" : address.toBase58().slice(0, 4);
+    const [pool] = getPoolAddress(
+      platformConfig,
+      mintA,
+      mintB,
+      program.programId
+    );
+    const [escrow] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("escrow"), pool.toBuffer()],
+      program.programId
+    );
+    const [lpMint] = web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("lp_mint"), pool.toBuffer()],
+      program.programId
+    );
+
+    await program.methods
+      .initialize(ONE, ONE, ZERO, null, 0, 0)
       .accounts({
         authority: provider.publicKey,
-        pool: texture.poolAB.publicKey,
-        referrer: referrer,
+        programState,
+        platformConfig,
+        pool,
+        mintA,
+        srcA: getAssociatedTokenAddress(
+          provider.publicKey,
+          mintA,
+          tokenProgramOf(mintA)
+        ),
+        treasuryA: getAssociatedTokenAddress(
+          escrow,
+          mintA,
+          tokenProgramOf(mintA)
+        ),
+        mintB,
+        srcB: getAssociatedTokenAddress(
+          provider.publicKey,
+          mintB,
+          tokenProgramOf(mintB)
+        ),
+        treasuryB: getAssociatedTokenAddress(
+          escrow,
+          mintB,
+          tokenProgramOf(mintB)
+        ),
+        lpMint,
+        metadataA: null,
+        metadataB: null,
+        dstLp: getLpTokenAccount(provider.publicKey, lpMint),
+        feeSrc: null,
+        feeDst: null,
+        escrow,
+        tokenRegistryA: getTokenRegistryAddress(mintA, program.programId)[0],
+        tokenRegistryB: getTokenRegistryAddress(mintB, program.programId)[0],
+        poolIndexA: getPoolIndexAddress(mintA, 0, program.programId)[0],
+        poolIndexB: getPoolIndexAddress(mintB, 0, program.programId)[0],
+        prevPoolIndexA: null,
+        prevPoolIndexB: null,
+        taxman: provider.publicKey,
+        tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: tokenProgramOf(mintA),
+        tokenProgramB: tokenProgramOf(mintB),
+        token2022Program: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
         rent: web3.SYSVAR_RENT_PUBKEY,
       })
       .rpc();
 
-    const { owner } = await program.account.referrer.fetch(referrer);
+    const { data } = await provider.connection.getAccountInfo(lpMint);
+    const name = `${symbolOf(mintA)}-${symbolOf(mintB)} LP`;
+    expect(data.includes(Buffer.from(name))).to.be.true;
+  });
 
-    console.log("owner.toBase58(): ", owner.toBase58());
+  it("reject listing a pool past an index page that is not full", async () => {
+    const { pool, mintA, mintB, treasuryA, treasuryB, lpMint, escrow } =
+      getPoolAccounts(texture.A.mint.publicKey, texture.C.mint.publicKey);
 
-    expect(owner).deep.eq(texture.Bob.keypair.publicKey);
+    try {
+      await program.methods
+        .initialize(ONE, ONE, ZERO, null, 1, 0)
+        .accounts({
+          authority: provider.publicKey,
+          programState,
+          platformConfig,
+          pool,
+          mintA,
+          srcA: utils.token.associatedAddress({
+            owner: provider.publicKey,
+            mint: mintA,
+          }),
+          treasuryA,
+          mintB,
+          srcB: utils.token.associatedAddress({
+            owner: provider.publicKey,
+            mint: mintB,
+          }),
+          treasuryB,
+          lpMint,
+          metadataA: null,
+          metadataB: null,
+          dstLp: getLpTokenAccount(provider.publicKey, lpMint),
+          feeSrc: null,
+          feeDst: null,
+          escrow,
+          tokenRegistryA: getTokenRegistryAddress(mintA, program.programId)[0],
+          tokenRegistryB: getTokenRegistryAddress(mintB, program.programId)[0],
+          poolIndexA: getPoolIndexAddress(mintA, 1, program.programId)[0],
+          poolIndexB: getPoolIndexAddress(mintB, 0, program.programId)[0],
+          prevPoolIndexA: getPoolIndexAddress(mintA, 0, program.programId)[0],
+          prevPoolIndexB: null,
+          taxman: provider.publicKey,
+          tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+          token2022Program: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+      expect.fail("the pool is listed on a sparse index page");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidIndexPage");
+    }
   });
 
-  it("Bob adds liquidity in the pool of (A,B)", async () => {
-    await program.methods
-      .addLiquidity(texture.A.amount.deposit, texture.B.amount.deposit)
+  /**
+   * Initialize a pool of the mint A and a new Token-2022 mint, paid by the provider
+   */
+  const initializePoolOfNewMint = async (
+    lpFee: BN | null,
+    taxman: web3.PublicKey,
+    feeSrc: web3.PublicKey | null,
+    feeDst: web3.PublicKey | null
+  ) => {
+    const mint = web3.Keypair.generate();
+    await initializeToken2022MintWithMetadata(
+      6,
+      mint,
+      "Fee",
+      "FEE",
+      new BN(1_000_000),
+      provider
+    );
+    const { pool, mintA, mintB, treasuryA, treasuryB, lpMint, escrow } =
+      getPoolAccounts(mint.publicKey, texture.A.mint.publicKey);
+    const tokenProgramOf = (address: web3.PublicKey) =>
+      address.equals(texture.A.mint.publicKey)
+        ? utils.token.TOKEN_PROGRAM_ID
+        : TOKEN_2022_PROGRAM_ID;
+
+    return await program.methods
+      .initialize(ONE, ONE, ZERO, lpFee, 0, 0)
       .accounts({
-        authority: texture.Bob.keypair.publicKey,
-        pool: texture.poolAB.publicKey,
-        mintA: texture.A.mint.publicKey,
-        srcA: texture.Bob.tokenAccount(texture.A.mint.publicKey),
-        treasuryA: utils.token.associatedAddress({
-          mint: texture.A.mint.publicKey,
-          owner: escrowAB,
-        }),
-        mintB: texture.B.mint.publicKey,
-        srcB: texture.Bob.tokenAccount(texture.B.mint.publicKey),
-        treasuryB: utils.token.associatedAddress({
-          mint: texture.B.mint.publicKey,
-          owner: escrowAB,
-        }),
-        lpMint: lpMintAB,
-        dstLp: texture.Bob.tokenAccount(lpMintAB),
-        escrow: escrowAB,
+        authority: provider.publicKey,
+        programState,
+        platformConfig,
+        pool,
+        mintA,
+        srcA: getAssociatedTokenAddress(
+          provider.publicKey,
+          mintA,
+          tokenProgramOf(mintA)
+        ),
+        treasuryA: getAssociatedTokenAddress(
+          escrow,
+          mintA,
+          tokenProgramOf(mintA)
+        ),
+        mintB,
+        srcB: getAssociatedTokenAddress(
+          provider.publicKey,
+          mintB,
+          tokenProgramOf(mintB)
+        ),
+        treasuryB: getAssociatedTokenAddress(
+          escrow,
+          mintB,
+          tokenProgramOf(mintB)
+        ),
+        lpMint,
+        metadataA: null,
+        metadataB: null,
+        dstLp: getLpTokenAccount(provider.publicKey, lpMint),
+        feeSrc,
+        feeDst,
+        escrow,
+        tokenRegistryA: getTokenRegistryAddress(mintA, program.programId)[0],
+        tokenRegistryB: getTokenRegistryAddress(mintB, program.programId)[0],
+        poolIndexA: getPoolIndexAddress(mintA, 0, program.programId)[0],
+        poolIndexB: getPoolIndexAddress(mintB, 0, program.programId)[0],
+        prevPoolIndexA: null,
+        prevPoolIndexB: null,
+        taxman,
         tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: tokenProgramOf(mintA),
+        tokenProgramB: tokenProgramOf(mintB),
+        token2022Program: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
         rent: web3.SYSVAR_RENT_PUBKEY,
       })
-      .signers([texture.Bob.keypair])
       .rpc();
+  };
+
+  // Alice collects the creation fees, so that her balances move by the fees only
+  const CREATE_POOL_FEE = new BN(10_000_000);
+  const CUSTOM_FEE_SURCHARGE = new BN(5_000_000);
+  // Above the bound of the customized lp fees
+  const CUSTOM_LP_FEE = new BN(100_000_000);
+
+  it("charge the pool creation fee in SOL", async () => {
+    const feeReceiver = texture.Alice.keypair.publicKey;
     await program.methods
-      .addLiquidity(texture.A.amount.deposit, texture.B.amount.deposit)
+      .updateFeeReceiver(feeReceiver)
       .accounts({
-        authority: texture.Bob.keypair.publicKey,
-        pool: texture.poolAB.publicKey,
-        mintA: texture.A.mint.publicKey,
-        srcA: texture.Bob.tokenAccount(texture.A.mint.publicKey),
-        treasuryA: utils.token.associatedAddress({
-          mint: texture.A.mint.publicKey,
-          owner: escrowAB,
-        }),
-        mintB: texture.B.mint.publicKey,
-        srcB: texture.Bob.tokenAccount(texture.B.mint.publicKey),
-        treasuryB: utils.token.associatedAddress({
-          mint: texture.B.mint.publicKey,
-          owner: escrowAB,
-        }),
-        lpMint: lpMintAB,
-        dstLp: texture.Bob.tokenAccount(lpMintAB),
-        escrow: escrowAB,
-        tokenProgram: utils.token.TOKEN_PROGRAM_ID,
-        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
-        systemProgram: web3.SystemProgram.programId,
-        rent: web3.SYSVAR_RENT_PUBKEY,
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
       })
-      .signers([texture.Bob.keypair])
-      .rpc({ skipPreflight: true });
+      .rpc();
+    await program.methods
+      .updateCreatePoolFee(
+        CREATE_POOL_FEE,
+        CUSTOM_FEE_SURCHARGE,
+        web3.PublicKey.default
+      )
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
+      })
+      .rpc();
 
-    const { amount } = await texture.spl.account.account.fetch(
-      texture.Bob.tokenAccount(lpMintAB)
+    // The default lp fee pays the base fee only
+    const prevBalance = await getLamports(feeReceiver, provider);
+    await initializePoolOfNewMint(null, feeReceiver, null, null);
+    const balance = await getLamports(feeReceiver, provider);
+    expect(balance - prevBalance).equal(CREATE_POOL_FEE.toNumber());
+
+    // A customized lp fee pays the surcharge on top
+    await initializePoolOfNewMint(CUSTOM_LP_FEE, feeReceiver, null, null);
+    const nextBalance = await getLamports(feeReceiver, provider);
+    expect(nextBalance - balance).equal(
+      CREATE_POOL_FEE.add(CUSTOM_FEE_SURCHARGE).toNumber()
     );
+  });
 
-    expect(amount.toString()).equal("400000000");
+  it("charge the pool creation fee in an SPL token", async () => {
+    const feeReceiver = texture.Alice.keypair.publicKey;
+    const feeSrc = utils.token.associatedAddress({
+      owner: provider.publicKey,
+      mint: texture.C.mint.publicKey,
+    });
+    const feeDst = texture.Alice.tokenAccount(texture.C.mint.publicKey);
+    await program.methods
+      .updateCreatePoolFee(
+        CREATE_POOL_FEE,
+        CUSTOM_FEE_SURCHARGE,
+        texture.C.mint.publicKey
+      )
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
+      })
+      .rpc();
+
+    const prevLamports = await getLamports(feeReceiver, provider);
+    const prevBalance = await getTokenBalance(feeDst, provider);
+    await initializePoolOfNewMint(CUSTOM_LP_FEE, feeReceiver, feeSrc, feeDst);
+    const lamports = await getLamports(feeReceiver, provider);
+    const balance = await getTokenBalance(feeDst, provider);
+    expect(
+      balance.sub(prevBalance).eq(CREATE_POOL_FEE.add(CUSTOM_FEE_SURCHARGE))
+    ).to.be.true;
+    // Nothing is charged in SOL on top
+    expect(lamports).equal(prevLamports);
   });
 
-  it("Bob removes liquidity out the pool of (A,B)", async () => {
-    // Prev states
-    const { amount: prevLP } = await texture.spl.account.account.fetch(
-      texture.Bob.tokenAccount(lpMintAB)
-    );
-    const { amount: prevA } = await texture.spl.account.account.fetch(
-      texture.Bob.tokenAccount(texture.A.mint.publicKey)
+  it("reject an SPL creation fee without the fee accounts", async () => {
+    const feeReceiver = texture.Alice.keypair.publicKey;
+    try {
+      await initializePoolOfNewMint(null, feeReceiver, null, null);
+      expect.fail("the pool is created without paying its creation fee");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("MissingFeeAccount");
+    }
+
+    // Pools are created for free again, with fees to the admin
+    await program.methods
+      .updateCreatePoolFee(ZERO, ZERO, web3.PublicKey.default)
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
+      })
+      .rpc();
+    await program.methods
+      .updateFeeReceiver(provider.publicKey)
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
+      })
+      .rpc();
+    const { createPoolFee, customFeeSurcharge, feeReceiver: restored } =
+      await program.account.platformConfig.fetch(platformConfig);
+    expect(createPoolFee.eq(ZERO)).to.be.true;
+    expect(customFeeSurcharge.eq(ZERO)).to.be.true;
+    expect(restored).deep.equal(provider.publicKey);
+  });
+
+  it("reject a pool of a denylisted mint", async () => {
+    const [tokenRegistry] = getTokenRegistryAddress(
+      texture.A.mint.publicKey,
+      program.programId
     );
-    const { amount: prevB } = await texture.spl.account.account.fetch(
-      texture.Bob.tokenAccount(texture.B.mint.publicKey)
+    const txId = await program.methods
+      .setTokenStatus({ denied: {} })
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        mint: texture.A.mint.publicKey,
+        tokenRegistry,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const { mint, status } = await program.account.tokenRegistry.fetch(
+      tokenRegistry
     );
+    expect(mint).deep.equal(texture.A.mint.publicKey);
+    expect(status).deep.equal({ denied: {} });
+    // The first listing moves the mint out of the unlisted status
+    const events = await getEvents(txId, program as any);
+    const { data } = events.find(({ name }) => name === "SetTokenStatusEvent");
+    expect(data.oldStatus).deep.equal({ unlisted: {} });
+    expect(data.status).deep.equal({ denied: {} });
 
-    const { amount: vaultA } = await texture.spl.account.account.fetch(
-      utils.token.associatedAddress({
+    try {
+      await initializePoolOfNewMint(null, provider.publicKey, null, null);
+      expect.fail("a pool of a denylisted mint is created");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("TokenNotAllowed");
+    }
+
+    const nextTxId = await program.methods
+      .setTokenStatus({ allowed: {} })
+      .accounts({
+        owner: provider.publicKey,
+        programState,
         mint: texture.A.mint.publicKey,
-        owner: escrowAB,
+        tokenRegistry,
+        systemProgram: web3.SystemProgram.programId,
       })
+      .rpc();
+    const { status: nextStatus } = await program.account.tokenRegistry.fetch(
+      tokenRegistry
     );
+    expect(nextStatus).deep.equal({ allowed: {} });
+    const nextEvents = await getEvents(nextTxId, program as any);
+    const { data: nextData } = nextEvents.find(
+      ({ name }) => name === "SetTokenStatusEvent"
+    );
+    expect(nextData.oldStatus).deep.equal({ denied: {} });
+  });
 
-    const { amount: vaultB } = await texture.spl.account.account.fetch(
-      utils.token.associatedAddress({
-        mint: texture.B.mint.publicKey,
-        owner: escrowAB,
+  it("reject a pool of an unlisted mint in strict mode", async () => {
+    await program.methods
+      .updateTokenPolicy(true)
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
       })
+      .rpc();
+    const { strictTokenList } = await program.account.platformConfig.fetch(
+      platformConfig
     );
+    expect(strictTokenList).to.be.true;
 
-    // Remove liquidity
-    const lp = HUNDRED.mul(new BN(1000000));
+    // The mint A is allowed, the new mint was never listed
+    try {
+      await initializePoolOfNewMint(null, provider.publicKey, null, null);
+      expect.fail("a pool of an unlisted mint is created in strict mode");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("TokenNotAllowed");
+    }
 
     await program.methods
-      .removeLiquidity(lp)
+      .updateTokenPolicy(false)
       .accounts({
-        authority: texture.Bob.keypair.publicKey,
-        pool: texture.poolAB.publicKey,
-        mintA: texture.A.mint.publicKey,
-        treasuryA: utils.token.associatedAddress({
-          mint: texture.A.mint.publicKey,
-          owner: escrowAB,
-        }),
-        dstA: texture.Bob.tokenAccount(texture.A.mint.publicKey),
-        mintB: texture.B.mint.publicKey,
-        treasuryB: utils.token.associatedAddress({
-          mint: texture.B.mint.publicKey,
-          owner: escrowAB,
-        }),
-        dstB: texture.Bob.tokenAccount(texture.B.mint.publicKey),
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
+      })
+      .rpc();
+    const { strictTokenList: nextStrictTokenList } =
+      await program.account.platformConfig.fetch(platformConfig);
+    expect(nextStrictTokenList).to.be.false;
+  });
+
+  it("update the LP uri of the pool of (A,B)", async () => {
+    const uri = "https://shield.dex/lp/ab.json";
+    await program.methods
+      .updateLpUri(uri)
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
         lpMint: lpMintAB,
-        srcLp: texture.Bob.tokenAccount(lpMintAB),
         escrow: escrowAB,
-        tokenProgram: utils.token.TOKEN_PROGRAM_ID,
-        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        token2022Program: TOKEN_2022_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
-        rent: web3.SYSVAR_RENT_PUBKEY,
       })
-      .signers([texture.Bob.keypair])
-      .rpc({ skipPreflight: true });
-    // Next states
-    const { amount: nextLP } = await texture.spl.account.account.fetch(
-      texture.Bob.tokenAccount(lpMintAB)
+      .rpc();
+
+    const { data } = await provider.connection.getAccountInfo(lpMintAB);
+    expect(data.includes(Buffer.from(uri))).to.be.true;
+    expect(data.includes(Buffer.from("SLP"))).to.be.true;
+  });
+
+  it("listed the pool of (A,B) under both mints", async () => {
+    const { mint, page, pools } = await program.account.poolIndex.fetch(
+      getPoolIndexAddress(texture.A.mint.publicKey, 0, program.programId)[0]
     );
-    const { amount: nextA } = await texture.spl.account.account.fetch(
-      texture.Bob.tokenAccount(texture.A.mint.publicKey)
+    const { pools: poolsB } = await program.account.poolIndex.fetch(
+      getPoolIndexAddress(texture.B.mint.publicKey, 0, program.programId)[0]
     );
-    const { amount: nextB } = await texture.spl.account.account.fetch(
-      texture.Bob.tokenAccount(texture.B.mint.publicKey)
+
+    expect(mint).deep.equal(texture.A.mint.publicKey);
+    expect(page).equal(0);
+    expect(pools).deep.equal([poolAB]);
+    expect(poolsB).deep.equal([poolAB]);
+  });
+
+  it("create referrer", async () => {
+    // Alice refers Bob, who refers the deployer
+    const [aliceReferrer] = await getReferrerAddress(
+      texture.Alice.keypair.publicKey,
+      poolAB,
+      program.programId
+    );
+    const [bobReferrer] = await getReferrerAddress(
+      texture.Bob.keypair.publicKey,
+      poolAB,
+      program.programId
+    );
+    const [referrer] = await getReferrerAddress(
+      provider.publicKey,
+      poolAB,
+      program.programId
     );
 
-    expect(prevLP.sub(nextLP).eq(lp)).to.be.true;
-    expect(nextA.sub(prevA).toString()).equal("100000000");
-    expect(nextB.sub(prevB).toString()).equal("100000000");
+    await program.methods
+      .createReferrer(texture.Alice.keypair.publicKey)
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        pool: poolAB,
+        referrer: bobReferrer,
+        parent: aliceReferrer,
+        referralStats: getReferralStatsAddress(
+          texture.Alice.keypair.publicKey,
+          program.programId
+        )[0],
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+    await program.methods
+      .createReferrer(texture.Bob.keypair.publicKey)
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        referrer: referrer,
+        parent: bobReferrer,
+        referralStats: getReferralStatsAddress(
+          texture.Bob.keypair.publicKey,
+          program.programId
+        )[0],
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const { owner, pool, parent } = await program.account.referrer.fetch(
+      referrer
+    );
+
+    expect(owner).deep.eq(texture.Bob.keypair.publicKey);
+    expect(pool).deep.eq(poolAB);
+    expect(parent).deep.eq(bobReferrer);
   });
 
-  // /**
-  //  * Alice swap 100 * 10^6 A to ? B
-  //  */
-  it("Alice swaps A to B", async () => {
-    const poolAA = await program.account.pool.fetch(texture.poolAB.publicKey);
-    console.log("poolAA", poolAA.tax.toBase58());
-    console.log(
-      "texture.platformConfig.publicKey: ",
-      texture.platformConfig.publicKey.toBase58()
+  it("reject a referral cycle", async () => {
+    const [aliceReferrer] = await getReferrerAddress(
+      texture.Alice.keypair.publicKey,
+      poolAB,
+      program.programId
     );
-    // Previous state
-    const { amount: prevA } = await texture.spl.account.account.fetch(
-      texture.Alice.tokenAccount(texture.A.mint.publicKey)
+    const [bobReferrer] = await getReferrerAddress(
+      texture.Bob.keypair.publicKey,
+      poolAB,
+      program.programId
     );
-    const { amount: prevB } = await texture.spl.account.account.fetch(
-      texture.Alice.tokenAccount(texture.B.mint.publicKey)
+    const [referrer] = await getReferrerAddress(
+      provider.publicKey,
+      poolAB,
+      program.programId
     );
-    // const prevTax = ZERO;
-    const { amount: prevTax } = await texture.spl.account.account.fetch(
-      utils.token.associatedAddress({
-        mint: texture.A.mint.publicKey,
-        owner: new web3.PublicKey(
-          "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
-        ),
+
+    try {
+      // Alice would earn from her own swaps as the referrer of Bob
+      await program.methods
+        .createReferrer(provider.publicKey)
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          pool: poolAB,
+          referrer: aliceReferrer,
+          parent: referrer,
+          referralStats: getReferralStatsAddress(
+            provider.publicKey,
+            program.programId
+          )[0],
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .remainingAccounts([
+          { pubkey: bobReferrer, isSigner: false, isWritable: false },
+        ])
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the referral cycle is accepted");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("ReferralCycle");
+    }
+  });
+
+  it("create a global referrer", async () => {
+    const [referrer] = await getReferrerAddress(
+      provider.publicKey,
+      null,
+      program.programId
+    );
+
+    await program.methods
+      .createReferrer(texture.Alice.keypair.publicKey)
+      .accounts({
+        authority: provider.publicKey,
+        pool: null,
+        referrer: referrer,
+        parent: (
+          await getReferrerAddress(
+            texture.Alice.keypair.publicKey,
+            null,
+            program.programId
+          )
+        )[0],
+        referralStats: getReferralStatsAddress(
+          texture.Alice.keypair.publicKey,
+          program.programId
+        )[0],
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const { owner, pool } = await program.account.referrer.fetch(referrer);
+
+    expect(owner).deep.eq(texture.Alice.keypair.publicKey);
+    expect(pool).deep.eq(web3.PublicKey.default);
+  });
+
+  it("Bob registers his referral code", async () => {
+    const [referralCode] = getReferralCodeAddress("BOB10", program.programId);
+
+    await program.methods
+      .registerCode("BOB10")
+      .accounts({
+        owner: texture.Bob.keypair.publicKey,
+        referralCode,
+        systemProgram: web3.SystemProgram.programId,
       })
+      .signers([texture.Bob.keypair])
+      .rpc();
+
+    const { owner, code } = await program.account.referralCode.fetch(
+      referralCode
     );
-    // Swap
-    const bidAmount = TEN.pow(new BN(texture.A.decimals)).mul(HUNDRED);
-    const limit = new BN(0); // Free slipagge rate
+    expect(owner).deep.eq(texture.Bob.keypair.publicKey);
+    expect(code).equal("BOB10");
+  });
+
+  it("reject a referral code out of the charset", async () => {
+    const [referralCode] = getReferralCodeAddress("bob-10", program.programId);
+
+    try {
+      await program.methods
+        .registerCode("bob-10")
+        .accounts({
+          owner: texture.Bob.keypair.publicKey,
+          referralCode,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([texture.Bob.keypair])
+        .rpc();
+      expect.fail("the referral code is accepted");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidReferralCode");
+    }
+  });
+
+  it("reject a referral code past the seed length", async () => {
+    // The client cannot derive the address of such a code either
+    const code = "BOB".repeat(11);
+
+    try {
+      await program.methods
+        .registerCode(code)
+        .accounts({
+          owner: texture.Bob.keypair.publicKey,
+          referralCode: web3.Keypair.generate().publicKey,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([texture.Bob.keypair])
+        .rpc();
+      expect.fail("the referral code is accepted");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidReferralCode");
+    }
+  });
+
+  it("Alice binds to Bob by his referral code", async () => {
+    const [referralCode] = getReferralCodeAddress("BOB10", program.programId);
+    const [referrer] = await getReferrerAddress(
+      texture.Alice.keypair.publicKey,
+      null,
+      program.programId
+    );
+
     await program.methods
-      .swap(bidAmount, limit)
+      .createReferrerByCode("BOB10")
       .accounts({
         authority: texture.Alice.keypair.publicKey,
-        pool: texture.poolAB.publicKey,
-        platformConfig: texture.platformConfig.publicKey,
-        bidMint: texture.A.mint.publicKey,
-        bidSrc: texture.Alice.tokenAccount(texture.A.mint.publicKey),
-        bidTreasury: utils.token.associatedAddress({
+        pool: null,
+        referralCode,
+        referrer,
+        parent: (
+          await getReferrerAddress(
+            texture.Bob.keypair.publicKey,
+            null,
+            program.programId
+          )
+        )[0],
+        referralStats: getReferralStatsAddress(
+          texture.Bob.keypair.publicKey,
+          program.programId
+        )[0],
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([texture.Alice.keypair])
+      .rpc();
+
+    const { owner, referee } = await program.account.referrer.fetch(referrer);
+    expect(owner).deep.eq(texture.Bob.keypair.publicKey);
+    expect(referee).deep.eq(texture.Alice.keypair.publicKey);
+  });
+
+  it("reject rebinding during the referrer cooldown", async () => {
+    const [referrer] = await getReferrerAddress(
+      texture.Alice.keypair.publicKey,
+      null,
+      program.programId
+    );
+    await program.methods
+      .updateReferrerCooldown(new BN(3600))
+      .accounts({ owner: provider.publicKey, programState, platformConfig })
+      .rpc();
+
+    try {
+      await program.methods
+        .updateReferrer(provider.publicKey)
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          pool: null,
+          platformConfig,
+          referrer,
+          parent: (
+            await getReferrerAddress(
+              provider.publicKey,
+              null,
+              program.programId
+            )
+          )[0],
+          referralStats: getReferralStatsAddress(
+            provider.publicKey,
+            program.programId
+          )[0],
+          previousReferralStats: getReferralStatsAddress(
+            texture.Bob.keypair.publicKey,
+            program.programId
+          )[0],
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the referrer is rebound during the cooldown");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("ReferrerCooldown");
+    }
+  });
+
+  it("Alice rebinds after the cooldown, never into a cycle", async () => {
+    const carol = web3.Keypair.generate();
+    const [referrer] = await getReferrerAddress(
+      texture.Alice.keypair.publicKey,
+      null,
+      program.programId
+    );
+    await program.methods
+      .updateReferrerCooldown(ZERO)
+      .accounts({ owner: provider.publicKey, programState, platformConfig })
+      .rpc();
+
+    try {
+      // The deployer is referred by Alice globally
+      await program.methods
+        .updateReferrer(provider.publicKey)
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          pool: null,
+          platformConfig,
+          referrer,
+          parent: (
+            await getReferrerAddress(
+              provider.publicKey,
+              null,
+              program.programId
+            )
+          )[0],
+          referralStats: getReferralStatsAddress(
+            provider.publicKey,
+            program.programId
+          )[0],
+          previousReferralStats: getReferralStatsAddress(
+            texture.Bob.keypair.publicKey,
+            program.programId
+          )[0],
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the referral cycle is accepted");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("ReferralCycle");
+    }
+
+    const [bobReferralStats] = getReferralStatsAddress(
+      texture.Bob.keypair.publicKey,
+      program.programId
+    );
+    const [carolReferralStats] = getReferralStatsAddress(
+      carol.publicKey,
+      program.programId
+    );
+    const { totalReferees: prevBobReferees } =
+      await program.account.referralStats.fetch(bobReferralStats);
+    await program.methods
+      .updateReferrer(carol.publicKey)
+      .accounts({
+        authority: texture.Alice.keypair.publicKey,
+        pool: null,
+        platformConfig,
+        referrer,
+        parent: (
+          await getReferrerAddress(carol.publicKey, null, program.programId)
+        )[0],
+        referralStats: carolReferralStats,
+        previousReferralStats: bobReferralStats,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([texture.Alice.keypair])
+      .rpc();
+
+    const { owner, parent } = await program.account.referrer.fetch(referrer);
+    expect(owner).deep.eq(carol.publicKey);
+    expect(parent).deep.eq(web3.PublicKey.default);
+    // Alice moved from Bob to Carol
+    const { totalReferees: bobReferees } =
+      await program.account.referralStats.fetch(bobReferralStats);
+    const { totalReferees: carolReferees } =
+      await program.account.referralStats.fetch(carolReferralStats);
+    expect(bobReferees.toNumber()).equal(prevBobReferees.toNumber() - 1);
+    expect(carolReferees.toNumber()).equal(1);
+
+    try {
+      await program.methods
+        .updateReferrer(carol.publicKey)
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          pool: null,
+          platformConfig,
+          referrer,
+          parent: (
+            await getReferrerAddress(carol.publicKey, null, program.programId)
+          )[0],
+          referralStats: carolReferralStats,
+          previousReferralStats: carolReferralStats,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the referrer is rebound to itself");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidReferer");
+    }
+  });
+
+  it("Alice unbinds then closes her global referrer", async () => {
+    const [referrer] = await getReferrerAddress(
+      texture.Alice.keypair.publicKey,
+      null,
+      program.programId
+    );
+    const { owner: carol } = await program.account.referrer.fetch(referrer);
+    const [carolReferralStats] = getReferralStatsAddress(
+      carol,
+      program.programId
+    );
+
+    await program.methods
+      .unbindReferrer()
+      .accounts({
+        authority: texture.Alice.keypair.publicKey,
+        referrer,
+        referralStats: carolReferralStats,
+      })
+      .signers([texture.Alice.keypair])
+      .rpc();
+    const { owner } = await program.account.referrer.fetch(referrer);
+    const { totalReferees } = await program.account.referralStats.fetch(
+      carolReferralStats
+    );
+    expect(owner).deep.eq(web3.PublicKey.default);
+    expect(totalReferees.toNumber()).equal(0);
+
+    await program.methods
+      .closeReferrer()
+      .accounts({
+        authority: texture.Alice.keypair.publicKey,
+        pool: null,
+        platformConfig,
+        referrer,
+        // Unbound, so Carol is not uncounted twice
+        referralStats: getReferralStatsAddress(
+          web3.PublicKey.default,
+          program.programId
+        )[0],
+      })
+      .signers([texture.Alice.keypair])
+      .rpc();
+    const { totalReferees: nextTotalReferees } =
+      await program.account.referralStats.fetch(carolReferralStats);
+    expect(nextTotalReferees.toNumber()).equal(0);
+    const info = await provider.connection.getAccountInfo(referrer);
+    expect(info).to.be.null;
+  });
+
+  it("Bob adds liquidity in the pool of (A,B)", async () => {
+    const txId = await program.methods
+      .addLiquidity(texture.A.amount.deposit, texture.B.amount.deposit)
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        pool: poolAB,
+        platformConfig,
+        mintA: texture.A.mint.publicKey,
+        srcA: texture.Bob.tokenAccount(texture.A.mint.publicKey),
+        treasuryA: utils.token.associatedAddress({
           mint: texture.A.mint.publicKey,
           owner: escrowAB,
         }),
-        askMint: texture.B.mint.publicKey,
-        askTreasury: utils.token.associatedAddress({
+        mintB: texture.B.mint.publicKey,
+        srcB: texture.Bob.tokenAccount(texture.B.mint.publicKey),
+        treasuryB: utils.token.associatedAddress({
           mint: texture.B.mint.publicKey,
           owner: escrowAB,
         }),
-        askDst: texture.Alice.tokenAccount(texture.B.mint.publicKey),
+        lpMint: lpMintAB,
+        dstLp: getLpTokenAccount(texture.Bob.keypair.publicKey, lpMintAB),
         escrow: escrowAB,
-        taxman: new web3.PublicKey(
-          "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
-        ),
-        taxDst: utils.token.associatedAddress({
+        tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+        lpTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+    await benchmark("addLiquidity", txId);
+    await program.methods
+      .addLiquidity(texture.A.amount.deposit, texture.B.amount.deposit)
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        pool: poolAB,
+        platformConfig,
+        mintA: texture.A.mint.publicKey,
+        srcA: texture.Bob.tokenAccount(texture.A.mint.publicKey),
+        treasuryA: utils.token.associatedAddress({
           mint: texture.A.mint.publicKey,
-          owner: new web3.PublicKey(
-            "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
-          ),
+          owner: escrowAB,
         }),
-        tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        mintB: texture.B.mint.publicKey,
+        srcB: texture.Bob.tokenAccount(texture.B.mint.publicKey),
+        treasuryB: utils.token.associatedAddress({
+          mint: texture.B.mint.publicKey,
+          owner: escrowAB,
+        }),
+        lpMint: lpMintAB,
+        dstLp: getLpTokenAccount(texture.Bob.keypair.publicKey, lpMintAB),
+        escrow: escrowAB,
+        tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+        lpTokenProgram: TOKEN_2022_PROGRAM_ID,
         associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
         systemProgram: web3.SystemProgram.programId,
         rent: web3.SYSVAR_RENT_PUBKEY,
       })
-      .signers([texture.Alice.keypair])
-      .rpc();
-    // Next state
-    const { amount: nextA } = await texture.spl.account.account.fetch(
-      texture.Alice.tokenAccount(texture.A.mint.publicKey)
+      .signers([texture.Bob.keypair])
+      .rpc({ skipPreflight: true });
+
+    const amount = await getTokenBalance(
+      getLpTokenAccount(texture.Bob.keypair.publicKey, lpMintAB),
+      provider
     );
-    const { amount: nextB } = await texture.spl.account.account.fetch(
-      texture.Alice.tokenAccount(texture.B.mint.publicKey)
+
+    expect(amount.toString()).equal("400000000");
+  });
+
+  it("Bob removes liquidity out the pool of (A,B)", async () => {
+    // Prev states
+    const prevLP = await getTokenBalance(
+      getLpTokenAccount(texture.Bob.keypair.publicKey, lpMintAB),
+      provider
     );
-    const { amount: nextTax } = await texture.spl.account.account.fetch(
+    const { amount: prevA } = await texture.spl.account.account.fetch(
+      texture.Bob.tokenAccount(texture.A.mint.publicKey)
+    );
+    const { amount: prevB } = await texture.spl.account.account.fetch(
+      texture.Bob.tokenAccount(texture.B.mint.publicKey)
+    );
+
+    const { amount: vaultA } = await texture.spl.account.account.fetch(
       utils.token.associatedAddress({
         mint: texture.A.mint.publicKey,
-        owner: new web3.PublicKey(
-          "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
-        ),
+        owner: escrowAB,
       })
     );
 
-    console.log("prevTax, nextTax: ", prevTax.toNumber(), nextTax.toNumber());
+    const { amount: vaultB } = await texture.spl.account.account.fetch(
+      utils.token.associatedAddress({
+        mint: texture.B.mint.publicKey,
+        owner: escrowAB,
+      })
+    );
 
-    expect(prevA.sub(nextA).eq(bidAmount)).to.be.true;
-    expect(nextB.sub(prevB).toString()).equal("99740054");
-    expect(nextTax.sub(prevTax).toString()).equal("250000");
+    // Remove liquidity
+    const lp = HUNDRED.mul(new BN(1000000));
+
+    const txId = await program.methods
+      .removeLiquidity(lp)
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        pool: poolAB,
+        platformConfig,
+        mintA: texture.A.mint.publicKey,
+        treasuryA: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: escrowAB,
+        }),
+        dstA: texture.Bob.tokenAccount(texture.A.mint.publicKey),
+        mintB: texture.B.mint.publicKey,
+        treasuryB: utils.token.associatedAddress({
+          mint: texture.B.mint.publicKey,
+          owner: escrowAB,
+        }),
+        dstB: texture.Bob.tokenAccount(texture.B.mint.publicKey),
+        lpMint: lpMintAB,
+        srcLp: getLpTokenAccount(texture.Bob.keypair.publicKey, lpMintAB),
+        escrow: escrowAB,
+        tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+        lpTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc({ skipPreflight: true });
+    await benchmark("removeLiquidity", txId);
+    // Next states
+    const nextLP = await getTokenBalance(
+      getLpTokenAccount(texture.Bob.keypair.publicKey, lpMintAB),
+      provider
+    );
+    const { amount: nextA } = await texture.spl.account.account.fetch(
+      texture.Bob.tokenAccount(texture.A.mint.publicKey)
+    );
+    const { amount: nextB } = await texture.spl.account.account.fetch(
+      texture.Bob.tokenAccount(texture.B.mint.publicKey)
+    );
+
+    expect(prevLP.sub(nextLP).eq(lp)).to.be.true;
+    expect(nextA.sub(prevA).toString()).equal("100000000");
+    expect(nextB.sub(prevB).toString()).equal("100000000");
+  });
+
+  // /**
+  //  * Alice swap 100 * 10^6 A to ? B
+  //  */
+  it("Alice swaps A to B", async () => {
+    const poolAA = await program.account.pool.fetch(poolAB);
+    console.log("poolAA", poolAA.tax.toBase58());
+    console.log("platformConfig: ", platformConfig.toBase58());
+    // Previous state
+    const { amount: prevA } = await texture.spl.account.account.fetch(
+      texture.Alice.tokenAccount(texture.A.mint.publicKey)
+    );
+    const { amount: prevB } = await texture.spl.account.account.fetch(
+      texture.Alice.tokenAccount(texture.B.mint.publicKey)
+    );
+    // const prevTax = ZERO;
+    const { amount: prevTax } = await texture.spl.account.account.fetch(
+      utils.token.associatedAddress({
+        mint: texture.A.mint.publicKey,
+        owner: new web3.PublicKey(
+          "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
+        ),
+      })
+    );
+    // Swap
+    const bidAmount = TEN.pow(new BN(texture.A.decimals)).mul(HUNDRED);
+    const limit = new BN(0); // Free slipagge rate
+    const txId = await program.methods
+      .swap(bidAmount, limit)
+      .accounts({
+        authority: texture.Alice.keypair.publicKey,
+        pool: poolAB,
+        swapCounter: swapCounterAB,
+        platformConfig,
+        bidMint: texture.A.mint.publicKey,
+        bidSrc: texture.Alice.tokenAccount(texture.A.mint.publicKey),
+        bidTreasury: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: escrowAB,
+        }),
+        askMint: texture.B.mint.publicKey,
+        askTreasury: utils.token.associatedAddress({
+          mint: texture.B.mint.publicKey,
+          owner: escrowAB,
+        }),
+        askDst: texture.Alice.tokenAccount(texture.B.mint.publicKey),
+        lpMint: lpMintAB,
+        escrow: escrowAB,
+        taxman: new web3.PublicKey(
+          "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
+        ),
+        taxDst: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: new web3.PublicKey(
+            "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
+          ),
+        }),
+        referrer: null,
+        referralRewards: null,
+        referralVault: null,
+        referralTreasury: null,
+        referralStats: null,
+        bidTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        askTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([texture.Alice.keypair])
+      .rpc();
+    await benchmark("swap", txId);
+    // Next state
+    const { amount: nextA } = await texture.spl.account.account.fetch(
+      texture.Alice.tokenAccount(texture.A.mint.publicKey)
+    );
+    const { amount: nextB } = await texture.spl.account.account.fetch(
+      texture.Alice.tokenAccount(texture.B.mint.publicKey)
+    );
+    const { amount: nextTax } = await texture.spl.account.account.fetch(
+      utils.token.associatedAddress({
+        mint: texture.A.mint.publicKey,
+        owner: new web3.PublicKey(
+          "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
+        ),
+      })
+    );
+
+    console.log("prevTax, nextTax: ", prevTax.toNumber(), nextTax.toNumber());
+
+    expect(prevA.sub(nextA).eq(bidAmount)).to.be.true;
+    expect(nextB.sub(prevB).toString()).equal("99740054");
+    expect(nextTax.sub(prevTax).toString()).equal("250000");
+    // Swap event
+    const events = await getEvents(txId, program as any);
+    const { data: swapEvent } = events.find(({ name }) => name === "SwapEvent");
+    const { amount: reserveA } = await texture.spl.account.account.fetch(
+      utils.token.associatedAddress({
+        mint: texture.A.mint.publicKey,
+        owner: escrowAB,
+      })
+    );
+    const { lpFeesMintA } = await program.account.pool.fetch(poolAB);
+    expect(swapEvent.askAmount.toString()).equal("99740054");
+    expect(swapEvent.tax.toString()).equal("250000");
+    expect(swapEvent.referralFee.toNumber()).equal(0);
+    expect(swapEvent.referrer.equals(web3.PublicKey.default)).to.be.true;
+    expect(swapEvent.reserveA.eq(reserveA.sub(lpFeesMintA))).to.be.true;
+    expect(swapEvent.lpSupply.gt(ZERO)).to.be.true;
+    expect(swapEvent.slot.gt(ZERO)).to.be.true;
+    // The first swap creates the shard of the swap counter
+    const { shard, totalSwaps, lastSwapAt } =
+      await program.account.swapCounter.fetch(swapCounterAB);
+    expect(shard).equal(0);
+    expect(totalSwaps.toNumber()).equal(1);
+    expect(lastSwapAt.eq(swapEvent.timestamp)).to.be.true;
+  });
+
+  it("Alice lean swaps A to B with existing accounts", async () => {
+    const taxDst = utils.token.associatedAddress({
+      mint: texture.A.mint.publicKey,
+      owner: new web3.PublicKey(
+        "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
+      ),
+    });
+    const { amount: prevA } = await texture.spl.account.account.fetch(
+      texture.Alice.tokenAccount(texture.A.mint.publicKey)
+    );
+    const { amount: prevB } = await texture.spl.account.account.fetch(
+      texture.Alice.tokenAccount(texture.B.mint.publicKey)
+    );
+
+    const bidAmount = TEN.pow(new BN(texture.A.decimals)).mul(HUNDRED);
+    const txId = await program.methods
+      .leanSwap(bidAmount, new BN(0))
+      .accounts({
+        authority: texture.Alice.keypair.publicKey,
+        platformConfig,
+        pool: poolAB,
+        swapCounter: swapCounterAB,
+        bidMint: texture.A.mint.publicKey,
+        bidSrc: texture.Alice.tokenAccount(texture.A.mint.publicKey),
+        bidTreasury: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: escrowAB,
+        }),
+        askMint: texture.B.mint.publicKey,
+        askTreasury: utils.token.associatedAddress({
+          mint: texture.B.mint.publicKey,
+          owner: escrowAB,
+        }),
+        askDst: texture.Alice.tokenAccount(texture.B.mint.publicKey),
+        taxDst,
+        lpMint: lpMintAB,
+        escrow: escrowAB,
+        bidTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        askTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+      })
+      .signers([texture.Alice.keypair])
+      .rpc();
+    await benchmark("leanSwap", txId);
+
+    const { amount: nextA } = await texture.spl.account.account.fetch(
+      texture.Alice.tokenAccount(texture.A.mint.publicKey)
+    );
+    const { amount: nextB } = await texture.spl.account.account.fetch(
+      texture.Alice.tokenAccount(texture.B.mint.publicKey)
+    );
+
+    expect(prevA.sub(nextA).eq(bidAmount)).to.be.true;
+    expect(nextB.gt(prevB)).to.be.true;
+    expect(computeUnits.leanSwap).lessThan(computeUnits.swap);
+    const { totalSwaps } = await program.account.swapCounter.fetch(
+      swapCounterAB
+    );
+    expect(totalSwaps.toNumber()).equal(2);
+  });
+
+  it("reject distributing the LP fees of the pool of (A,B) with swapped mints", async () => {
+    const treasury = (mint: web3.PublicKey) =>
+      utils.token.associatedAddress({ mint, owner: escrowAB });
+    const recipient = (mint: web3.PublicKey) =>
+      utils.token.associatedAddress({ mint, owner: provider.publicKey });
+    try {
+      await program.methods
+        .distributeLpFee(BILLION, BILLION)
+        .accounts({
+          owner: provider.publicKey,
+          programState,
+          escrow: escrowAB,
+          pool: poolAB,
+          accessControl: null,
+          platformConfig,
+          mintA: texture.B.mint.publicKey,
+          mintB: texture.A.mint.publicKey,
+          treasuryA: treasury(texture.B.mint.publicKey),
+          treasuryB: treasury(texture.A.mint.publicKey),
+          recipientTokenAAccount: recipient(texture.B.mint.publicKey),
+          recipientTokenBAccount: recipient(texture.A.mint.publicKey),
+          tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        })
+        .rpc();
+      expect.fail("the LP fees of a mint are paid in the other mint");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("UnmatchPool");
+    }
+  });
+
+  it("reject the former fee receiver once the fee receiver is updated", async () => {
+    const feeReceiver = texture.taxman.publicKey;
+    try {
+      await program.methods
+        .updateFeeReceiver(web3.PublicKey.default)
+        .accounts({
+          owner: provider.publicKey,
+          programState,
+          platformConfig,
+        })
+        .rpc();
+      expect.fail("the fees are sent to the default pubkey");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidParams");
+    }
+    await program.methods
+      .updateFeeReceiver(feeReceiver)
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
+      })
+      .rpc();
+    const { feeReceiver: nextFeeReceiver } =
+      await program.account.platformConfig.fetch(platformConfig);
+    expect(nextFeeReceiver).deep.equal(feeReceiver);
+
+    // The tax of a swap cannot go to the former fee receiver
+    try {
+      await program.methods
+        .swap(TEN.pow(new BN(texture.A.decimals)), ZERO)
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          pool: poolAB,
+          swapCounter: swapCounterAB,
+          platformConfig,
+          bidMint: texture.A.mint.publicKey,
+          bidSrc: texture.Alice.tokenAccount(texture.A.mint.publicKey),
+          bidTreasury: utils.token.associatedAddress({
+            mint: texture.A.mint.publicKey,
+            owner: escrowAB,
+          }),
+          askMint: texture.B.mint.publicKey,
+          askTreasury: utils.token.associatedAddress({
+            mint: texture.B.mint.publicKey,
+            owner: escrowAB,
+          }),
+          askDst: texture.Alice.tokenAccount(texture.B.mint.publicKey),
+          lpMint: lpMintAB,
+          escrow: escrowAB,
+          taxman: provider.publicKey,
+          taxDst: utils.token.associatedAddress({
+            mint: texture.A.mint.publicKey,
+            owner: provider.publicKey,
+          }),
+          referrer: null,
+          referralRewards: null,
+          referralVault: null,
+          referralTreasury: null,
+          referralStats: null,
+          bidTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+          askTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+          associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the tax goes to the former fee receiver");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidFeeReceiver");
+    }
+
+    // Neither can the creation fee of a pool
+    try {
+      await initializePoolOfNewMint(null, provider.publicKey, null, null);
+      expect.fail("the creation fee goes to the former fee receiver");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidFeeReceiver");
+    }
+
+    await program.methods
+      .updateFeeReceiver(provider.publicKey)
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
+      })
+      .rpc();
+  });
+
+  it("pause swaps and deposits in the pool of (A,B)", async () => {
+    await program.methods
+      .pause(PAUSE.Swap | PAUSE.Deposit)
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        accessControl: null,
+        platformConfig,
+      })
+      .rpc();
+
+    const { pauseFlags } = await program.account.pool.fetch(
+      poolAB
+    );
+
+    expect(pauseFlags).equal(PAUSE.Swap | PAUSE.Deposit);
+  });
+
+  it("resume swaps and deposits in the pool of (A,B)", async () => {
+    await program.methods
+      .resume(PAUSE.Swap | PAUSE.Deposit)
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        accessControl: null,
+      })
+      .rpc();
+
+    const { pauseFlags, state } = await program.account.pool.fetch(
+      poolAB
+    );
+
+    expect(pauseFlags).equal(0);
+    expect(state).deep.equal(STATE.Initialized);
+  });
+
+  it("the platform guardian pulls the kill switch but cannot release it", async () => {
+    await program.methods
+      .updateGuardian(texture.Bob.keypair.publicKey)
+      .accounts({ owner: provider.publicKey, programState, platformConfig })
+      .rpc();
+
+    await program.methods
+      .setEmergencyPause(true)
+      .accounts({
+        owner: texture.Bob.keypair.publicKey,
+        programState,
+        platformConfig,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+    const { emergencyPaused } = await program.account.platformConfig.fetch(
+      platformConfig
+    );
+    expect(emergencyPaused).to.be.true;
+
+    try {
+      await program.methods
+        .setEmergencyPause(false)
+        .accounts({
+        owner: texture.Bob.keypair.publicKey,
+        programState,
+        platformConfig,
+      })
+        .signers([texture.Bob.keypair])
+        .rpc();
+      expect.fail("the guardian releases the kill switch");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("Unauthorized");
+    }
+
+    await program.methods
+      .setEmergencyPause(false)
+      .accounts({ owner: provider.publicKey, programState, platformConfig })
+      .rpc();
+    const { emergencyPaused: nextEmergencyPaused } =
+      await program.account.platformConfig.fetch(platformConfig);
+    expect(nextEmergencyPaused).to.be.false;
+  });
+
+  it("the pool guardian pauses the pool of (A,B) but cannot resume it", async () => {
+    await program.methods
+      .updatePoolGuardian(texture.Alice.keypair.publicKey)
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        accessControl: null,
+      })
+      .rpc();
+
+    await program.methods
+      .pause(PAUSE.Swap)
+      .accounts({
+        authority: texture.Alice.keypair.publicKey,
+        pool: poolAB,
+        accessControl: null,
+        platformConfig,
+      })
+      .signers([texture.Alice.keypair])
+      .rpc();
+    const { pauseFlags } = await program.account.pool.fetch(poolAB);
+    expect(pauseFlags).equal(PAUSE.Swap);
+
+    try {
+      await program.methods
+        .resume(PAUSE.Swap)
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          pool: poolAB,
+          accessControl: null,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the guardian resumes the pool");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("Unauthorized");
+    }
+
+    await program.methods
+      .resume(PAUSE.Swap)
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        accessControl: null,
+      })
+      .rpc();
+    const { pauseFlags: nextPauseFlags } = await program.account.pool.fetch(
+      poolAB
+    );
+    expect(nextPauseFlags).equal(0);
+  });
+
+  it("a pool manager sets the guardian of the pool of (A,B) until revoked", async () => {
+    const [accessControl] = getAccessControlAddress(poolAB, program.programId);
+
+    await program.methods
+      .grantRole(texture.Bob.keypair.publicKey, ROLE.PoolManager)
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        accessControl,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .updatePoolGuardian(texture.Bob.keypair.publicKey)
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        pool: poolAB,
+        accessControl,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+    const { guardian } = await program.account.pool.fetch(poolAB);
+    expect(guardian).deep.equal(texture.Bob.keypair.publicKey);
+
+    await program.methods
+      .revokeRole(texture.Bob.keypair.publicKey, ROLE.PoolManager)
+      .accounts({ authority: provider.publicKey, pool: poolAB, accessControl })
+      .rpc();
+    try {
+      await program.methods
+        .updatePoolGuardian(web3.PublicKey.default)
+        .accounts({
+          authority: texture.Bob.keypair.publicKey,
+          pool: poolAB,
+          accessControl,
+        })
+        .signers([texture.Bob.keypair])
+        .rpc();
+      expect.fail("a revoked pool manager sets the guardian");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("Unauthorized");
+    }
+  });
+
+  it("propose and execute a fee change in the pool of (A,B)", async () => {
+    const [pendingChange] = getPendingChangeAddress(
+      poolAB,
+      PARAM_CHANGE.LpFee,
+      program.programId
+    );
+    const eta =
+      (await getCurrentTimestamp(provider.connection)) + TIMELOCK_DELAY;
+
+    await program.methods
+      .updateFee(texture.fee, new BN(eta))
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        accessControl: null,
+        platformConfig,
+        pendingChange,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    try {
+      await program.methods
+        .executeChange()
+        .accounts({
+          executor: texture.Alice.keypair.publicKey,
+          programState,
+          proposer: provider.publicKey,
+          pendingChange,
+          platformConfig,
+          pool: poolAB,
+          accessControl: null,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the change is executed before its eta");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("TimelockNotElapsed");
+    }
+
+    await waitUntil(provider.connection, eta);
+    await program.methods
+      .executeChange()
+      .accounts({
+        executor: texture.Alice.keypair.publicKey,
+        programState,
+        proposer: provider.publicKey,
+        pendingChange,
+        platformConfig,
+        pool: poolAB,
+        accessControl: null,
+      })
+      .signers([texture.Alice.keypair])
+      .rpc();
+
+    const { lpFee } = await program.account.pool.fetch(
+      poolAB
+    );
+    const info = await provider.connection.getAccountInfo(pendingChange);
+
+    expect(lpFee.eq(texture.fee)).to.be.true;
+    expect(info).to.be.null;
+  });
+
+  it("propose and execute a referral fee change in the pool of (A,B)", async () => {
+    const [pendingChange] = getPendingChangeAddress(
+      poolAB,
+      PARAM_CHANGE.ReferralFee,
+      program.programId
+    );
+    const eta =
+      (await getCurrentTimestamp(provider.connection)) + TIMELOCK_DELAY;
+    // Half of the lp fee goes to the referrer
+    const referralFee = BILLION.div(new BN(2));
+
+    await program.methods
+      .updateReferralFee(referralFee, new BN(eta))
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        accessControl: null,
+        platformConfig,
+        pendingChange,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    await waitUntil(provider.connection, eta);
+    await program.methods
+      .executeChange()
+      .accounts({
+        executor: provider.publicKey,
+        programState,
+        proposer: provider.publicKey,
+        pendingChange,
+        platformConfig,
+        pool: poolAB,
+        accessControl: null,
+      })
+      .rpc();
+
+    const { referralFee: nextReferralFee } = await program.account.pool.fetch(
+      poolAB
+    );
+    expect(nextReferralFee.eq(referralFee)).to.be.true;
+  });
+
+  it("the pool authority cancels a change whose proposer lost the role", async () => {
+    const [pendingChange] = getPendingChangeAddress(
+      poolAB,
+      PARAM_CHANGE.LpFee,
+      program.programId
+    );
+    const [accessControl] = getAccessControlAddress(poolAB, program.programId);
+    const now = await getCurrentTimestamp(provider.connection);
+
+    await program.methods
+      .grantRole(texture.Bob.keypair.publicKey, ROLE.FeeManager)
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        accessControl,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    // A change cannot hold its slot past the longest timelock
+    try {
+      await program.methods
+        .updateFee(ZERO, new BN(now + TIMELOCK_DELAY + MAX_TIMELOCK_DELAY))
+        .accounts({
+          authority: texture.Bob.keypair.publicKey,
+          pool: poolAB,
+          accessControl,
+          platformConfig,
+          pendingChange,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .signers([texture.Bob.keypair])
+        .rpc();
+      expect.fail("a change is queued past the longest timelock");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidParams");
+    }
+    await program.methods
+      .updateFee(ZERO, new BN(now + TIMELOCK_DELAY))
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        pool: poolAB,
+        accessControl,
+        platformConfig,
+        pendingChange,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+    await program.methods
+      .revokeRole(texture.Bob.keypair.publicKey, ROLE.FeeManager)
+      .accounts({ authority: provider.publicKey, pool: poolAB, accessControl })
+      .rpc();
+
+    try {
+      await program.methods
+        .cancelChange()
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          programState,
+          proposer: texture.Bob.keypair.publicKey,
+          pendingChange,
+          pool: poolAB,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the change is canceled by a stranger");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("Unauthorized");
+    }
+    const balance = await getLamports(texture.Bob.keypair.publicKey, provider);
+    await program.methods
+      .cancelChange()
+      .accounts({
+        authority: provider.publicKey,
+        programState,
+        proposer: texture.Bob.keypair.publicKey,
+        pendingChange,
+        pool: poolAB,
+      })
+      .rpc();
+
+    const info = await provider.connection.getAccountInfo(pendingChange);
+    const { lpFee } = await program.account.pool.fetch(poolAB);
+    expect(info).to.be.null;
+    expect(lpFee.eq(texture.fee)).to.be.true;
+    // The proposer gets the rent back
+    expect(
+      await getLamports(texture.Bob.keypair.publicKey, provider)
+    ).greaterThan(balance);
+  });
+
+  it("set the referral tiers of the platform config", async () => {
+    // A third of the referral fee goes to the referrer of the referrer
+    const shares = [BILLION.div(new BN(3)), ZERO];
+
+    await program.methods
+      .updateReferralTiers(shares)
+      .accounts({
+        owner: provider.publicKey,
+        programState,
+        platformConfig,
+      })
+      .rpc();
+
+    const { referralTierShares } = await program.account.platformConfig.fetch(
+      platformConfig
+    );
+    expect(referralTierShares[0].eq(shares[0])).to.be.true;
+  });
+
+  it("credit Bob and Alice the referral shares of a swap in the pool of (A,B)", async () => {
+    const taxman = new web3.PublicKey(
+      "CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1"
+    );
+    const [referrer] = await getReferrerAddress(
+      provider.publicKey,
+      poolAB,
+      program.programId
+    );
+    const [bobReferrer] = await getReferrerAddress(
+      texture.Bob.keypair.publicKey,
+      poolAB,
+      program.programId
+    );
+    const [referralRewards] = getReferralRewardsAddress(
+      texture.Bob.keypair.publicKey,
+      texture.A.mint.publicKey,
+      program.programId
+    );
+    const [aliceReferralRewards] = getReferralRewardsAddress(
+      texture.Alice.keypair.publicKey,
+      texture.A.mint.publicKey,
+      program.programId
+    );
+    const [referralStats] = getReferralStatsAddress(
+      texture.Bob.keypair.publicKey,
+      program.programId
+    );
+    const [aliceReferralStats] = getReferralStatsAddress(
+      texture.Alice.keypair.publicKey,
+      program.programId
+    );
+    const { lpFee, referralFee } = await program.account.pool.fetch(poolAB);
+    const { referralTierShares } = await program.account.platformConfig.fetch(
+      platformConfig
+    );
+
+    const bidAmount = TEN.pow(new BN(texture.A.decimals)).mul(HUNDRED);
+    const txId = await program.methods
+      .swap(bidAmount, new BN(0))
+      .accounts({
+        authority: provider.publicKey,
+        pool: poolAB,
+        swapCounter: swapCounterAB,
+        platformConfig,
+        bidMint: texture.A.mint.publicKey,
+        bidSrc: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: provider.publicKey,
+        }),
+        bidTreasury: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: escrowAB,
+        }),
+        askMint: texture.B.mint.publicKey,
+        askTreasury: utils.token.associatedAddress({
+          mint: texture.B.mint.publicKey,
+          owner: escrowAB,
+        }),
+        askDst: utils.token.associatedAddress({
+          mint: texture.B.mint.publicKey,
+          owner: provider.publicKey,
+        }),
+        lpMint: lpMintAB,
+        escrow: escrowAB,
+        taxman,
+        taxDst: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: taxman,
+        }),
+        referrer,
+        referralRewards,
+        referralVault,
+        referralTreasury: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: referralVault,
+        }),
+        referralStats,
+        bidTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        askTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .remainingAccounts([
+        { pubkey: bobReferrer, isSigner: false, isWritable: false },
+        { pubkey: aliceReferralRewards, isSigner: false, isWritable: true },
+        { pubkey: aliceReferralStats, isSigner: false, isWritable: true },
+      ])
+      .rpc();
+    await benchmark("swapWithReferrer", txId);
+
+    const fee = bidAmount.mul(lpFee).div(BILLION);
+    const referralAmount = fee.mul(referralFee).div(BILLION);
+    const uplineAmount = referralAmount.mul(referralTierShares[0]).div(BILLION);
+    const { owner, mint, accrued } =
+      await program.account.referralRewards.fetch(referralRewards);
+    const { accrued: aliceAccrued } =
+      await program.account.referralRewards.fetch(aliceReferralRewards);
+
+    expect(owner).deep.eq(texture.Bob.keypair.publicKey);
+    expect(mint).deep.eq(texture.A.mint.publicKey);
+    expect(accrued.eq(referralAmount.sub(uplineAmount))).to.be.true;
+    expect(aliceAccrued.eq(uplineAmount)).to.be.true;
+
+    // Bob referred the deployer in the pool of (A,B), Alice rebound from him to Carol
+    const { totalReferees, mints } =
+      await program.account.referralStats.fetch(referralStats);
+    const { mints: aliceMints } = await program.account.referralStats.fetch(
+      aliceReferralStats
+    );
+    expect(totalReferees.toNumber()).equal(1);
+    expect(mints[0].mint).deep.eq(texture.A.mint.publicKey);
+    expect(mints[0].volume.eq(bidAmount)).to.be.true;
+    expect(mints[0].rewards.eq(accrued)).to.be.true;
+    expect(aliceMints[0].volume.isZero()).to.be.true;
+    expect(aliceMints[0].rewards.eq(uplineAmount)).to.be.true;
+  });
+
+  it("Bob claims his referral rewards", async () => {
+    const [referralRewards] = getReferralRewardsAddress(
+      texture.Bob.keypair.publicKey,
+      texture.A.mint.publicKey,
+      program.programId
+    );
+    const dst = texture.Bob.tokenAccount(texture.A.mint.publicKey);
+    const { accrued } = await program.account.referralRewards.fetch(
+      referralRewards
+    );
+    const prevA = await getTokenBalance(dst, provider);
+
+    await program.methods
+      .claimReferralRewards()
+      .accounts({
+        owner: texture.Bob.keypair.publicKey,
+        mint: texture.A.mint.publicKey,
+        referralRewards,
+        referralVault,
+        referralTreasury: utils.token.associatedAddress({
+          mint: texture.A.mint.publicKey,
+          owner: referralVault,
+        }),
+        dst,
+        tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+
+    const nextA = await getTokenBalance(dst, provider);
+    const { accrued: nextAccrued, totalClaimed } =
+      await program.account.referralRewards.fetch(referralRewards);
+
+    expect(nextA.sub(prevA).eq(accrued)).to.be.true;
+    expect(nextAccrued.isZero()).to.be.true;
+    expect(totalClaimed.eq(accrued)).to.be.true;
+  });
+
+  it("credit the referral share a transfer fee mint leaves in the vault", async () => {
+    const mint = web3.Keypair.generate();
+    // A fee of 1% on every transfer
+    await initializeToken2022MintWithTransferFee(
+      6,
+      mint,
+      100,
+      TEN.pow(new BN(12)),
+      provider
+    );
+    const { pool, mintA, mintB, lpMint, escrow, poolIndexA, poolIndexB } =
+      getPoolAccounts(mint.publicKey, texture.A.mint.publicKey);
+    const tokenProgramOf = (address: web3.PublicKey) =>
+      address.equals(texture.A.mint.publicKey)
+        ? utils.token.TOKEN_PROGRAM_ID
+        : TOKEN_2022_PROGRAM_ID;
+    const tokenAccount = (owner: web3.PublicKey, address: web3.PublicKey) =>
+      getAssociatedTokenAddress(owner, address, tokenProgramOf(address));
+    const amount = TEN.pow(new BN(texture.A.decimals)).mul(THOUSAND);
+
+    // Half of a 1% lp fee goes to the referrers
+    await program.methods
+      .initialize(
+        amount,
+        amount,
+        BILLION.div(new BN(2)),
+        new BN(10_000_000),
+        0,
+        0
+      )
+      .accounts({
+        authority: provider.publicKey,
+        programState,
+        platformConfig,
+        pool,
+        mintA,
+        srcA: tokenAccount(provider.publicKey, mintA),
+        treasuryA: tokenAccount(escrow, mintA),
+        mintB,
+        srcB: tokenAccount(provider.publicKey, mintB),
+        treasuryB: tokenAccount(escrow, mintB),
+        lpMint,
+        metadataA: null,
+        metadataB: null,
+        dstLp: getLpTokenAccount(provider.publicKey, lpMint),
+        feeSrc: null,
+        feeDst: null,
+        escrow,
+        tokenRegistryA: getTokenRegistryAddress(mintA, program.programId)[0],
+        tokenRegistryB: getTokenRegistryAddress(mintB, program.programId)[0],
+        poolIndexA,
+        poolIndexB,
+        prevPoolIndexA: null,
+        prevPoolIndexB: null,
+        taxman: provider.publicKey,
+        tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: tokenProgramOf(mintA),
+        tokenProgramB: tokenProgramOf(mintB),
+        token2022Program: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    // Bob refers the deployer in the pool, at the top tier
+    const [referrer] = await getReferrerAddress(
+      provider.publicKey,
+      pool,
+      program.programId
+    );
+    const [bobReferrer] = await getReferrerAddress(
+      texture.Bob.keypair.publicKey,
+      pool,
+      program.programId
+    );
+    const [referralStats] = getReferralStatsAddress(
+      texture.Bob.keypair.publicKey,
+      program.programId
+    );
+    await program.methods
+      .createReferrer(texture.Bob.keypair.publicKey)
+      .accounts({
+        authority: provider.publicKey,
+        pool,
+        referrer,
+        parent: bobReferrer,
+        referralStats,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const [referralRewards] = getReferralRewardsAddress(
+      texture.Bob.keypair.publicKey,
+      mint.publicKey,
+      program.programId
+    );
+    const referralTreasury = tokenAccount(referralVault, mint.publicKey);
+    const { id, lpFee, referralFee } = await program.account.pool.fetch(pool);
+    const bidAmount = TEN.pow(new BN(6)).mul(HUNDRED);
+    await program.methods
+      .swap(bidAmount, ZERO)
+      .accounts({
+        authority: provider.publicKey,
+        pool,
+        swapCounter: getSwapCounterAddress(id, program.programId)[0],
+        platformConfig,
+        bidMint: mint.publicKey,
+        bidSrc: tokenAccount(provider.publicKey, mint.publicKey),
+        bidTreasury: tokenAccount(escrow, mint.publicKey),
+        askMint: texture.A.mint.publicKey,
+        askTreasury: tokenAccount(escrow, texture.A.mint.publicKey),
+        askDst: tokenAccount(provider.publicKey, texture.A.mint.publicKey),
+        lpMint,
+        escrow,
+        taxman: provider.publicKey,
+        taxDst: tokenAccount(provider.publicKey, mint.publicKey),
+        referrer,
+        referralRewards,
+        referralVault,
+        referralTreasury,
+        referralStats,
+        bidTokenProgram: TOKEN_2022_PROGRAM_ID,
+        askTokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    // Bob is credited what the vault holds, short of the transfer fee
+    const referralAmount = bidAmount
+      .mul(lpFee)
+      .div(BILLION)
+      .mul(referralFee)
+      .div(BILLION);
+    const { accrued } = await program.account.referralRewards.fetch(
+      referralRewards
+    );
+    const vaultBalance = await getTokenBalance(referralTreasury, provider);
+    expect(accrued.eq(vaultBalance)).to.be.true;
+    expect(accrued.lt(referralAmount)).to.be.true;
+  });
+
+  it("reject migrating a pool already at the current version", async () => {
+    const { version } = await program.account.pool.fetch(poolAB);
+    expect(version).equal(2);
+
+    try {
+      await program.methods
+        .migratePool(0, 0)
+        .accounts({
+          authority: provider.publicKey,
+          programState,
+          pool: poolAB,
+          mintA: texture.A.mint.publicKey,
+          mintB: texture.B.mint.publicKey,
+          poolIndexA: getPoolIndexAddress(
+            texture.A.mint.publicKey,
+            0,
+            program.programId
+          )[0],
+          poolIndexB: getPoolIndexAddress(
+            texture.B.mint.publicKey,
+            0,
+            program.programId
+          )[0],
+          prevPoolIndexA: null,
+          prevPoolIndexB: null,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("the pool is already migrated");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("AlreadyMigrated");
+    }
+  });
+
+  it("migrate a pool in the baseline layout", async () => {
+    const { totalPools } = await program.account.programState.fetch(
+      programState
+    );
+
+    await program.methods
+      .migratePool(0, 0)
+      .accounts({
+        authority: provider.publicKey,
+        programState,
+        pool: FIXTURES.poolV0,
+        mintA: FIXTURES.mintX,
+        mintB: FIXTURES.mintY,
+        poolIndexA: getPoolIndexAddress(
+          FIXTURES.mintX,
+          0,
+          program.programId
+        )[0],
+        poolIndexB: getPoolIndexAddress(
+          FIXTURES.mintY,
+          0,
+          program.programId
+        )[0],
+        prevPoolIndexA: null,
+        prevPoolIndexB: null,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const {
+      id,
+      authority,
+      mintA,
+      mintB,
+      lpFee,
+      tax,
+      state,
+      riskFlags,
+      version,
+      createdAt,
+    } = await program.account.pool.fetch(FIXTURES.poolV0);
+    const { totalPools: nextTotalPools } =
+      await program.account.programState.fetch(programState);
+
+    expect(id.eq(totalPools)).to.be.true;
+    expect(nextTotalPools.eq(totalPools.add(ONE))).to.be.true;
+    expect(authority).deep.equal(FIXTURES.authority);
+    expect(mintA).deep.equal(FIXTURES.mintX);
+    expect(mintB).deep.equal(FIXTURES.mintY);
+    expect(lpFee.toNumber()).equal(2500000);
+    expect(tax).deep.equal(FIXTURES.platformConfigV0);
+    expect(state).equal(STATE.Initialized);
+    // The fixture mints keep their mint authority only
+    expect(riskFlags).equal(RISK.MintAuthority);
+    expect(version).equal(2);
+    expect(createdAt.toNumber()).equal(1700000000);
+    // Baseline pools predate the index and get listed on migration
+    const { pools } = await program.account.poolIndex.fetch(
+      getPoolIndexAddress(FIXTURES.mintX, 0, program.programId)[0]
+    );
+    expect(pools.some((pool) => pool.equals(FIXTURES.poolV0))).to.be.true;
+  });
+
+  it("migrate a platform config in the baseline layout", async () => {
+    const { totalConfigs } = await program.account.programState.fetch(
+      programState
+    );
+
+    await program.methods
+      .migratePlatformConfig()
+      .accounts({
+        authority: provider.publicKey,
+        platformConfig: FIXTURES.platformConfigV0,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const { index, tax, maxLpFee, feeReceiver, version, createdAt } =
+      await program.account.platformConfig.fetch(FIXTURES.platformConfigV0);
+    const { totalConfigs: nextTotalConfigs } =
+      await program.account.programState.fetch(programState);
+
+    // The baseline config keeps its address, out of the index space of the seeded configs
+    expect(index).equal(LEGACY_PLATFORM_CONFIG_INDEX);
+    expect(nextTotalConfigs).equal(totalConfigs);
+    const [seededConfig] = getPlatformConfigAddress(
+      nextTotalConfigs - 1,
+      program.programId
+    );
+    const { index: seededIndex } = await program.account.platformConfig.fetch(
+      seededConfig
+    );
+    expect(seededIndex).equal(nextTotalConfigs - 1);
+    expect(tax.toNumber()).equal(2500000);
+    expect(maxLpFee.eq(BILLION)).to.be.true;
+    expect(feeReceiver).deep.equal(
+      new web3.PublicKey("CVkbpNdrD1hb6TDwiyaoEyrDUft4T7aM5PQifmtCnGb1")
+    );
+    expect(version).equal(1);
+    expect(createdAt.toNumber()).equal(1700000000);
+
+    try {
+      await program.methods
+        .migratePlatformConfig()
+        .accounts({
+          authority: provider.publicKey,
+          platformConfig: FIXTURES.platformConfigV0,
+          systemProgram: web3.SystemProgram.programId,
+        })
+        .rpc();
+      expect.fail("the platform config is migrated twice");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("AlreadyMigrated");
+    }
+  });
+
+  it("move a referrer in the baseline layout to its global binding", async () => {
+    const [referrer] = await getReferrerAddress(
+      FIXTURES.referee,
+      null,
+      program.programId
+    );
+    const legacyRent = await getLamports(FIXTURES.referrerV0, provider);
+    const refereeBalance = await getLamports(FIXTURES.referee, provider);
+
+    await program.methods
+      .migrateReferrer()
+      .accounts({
+        authority: provider.publicKey,
+        referee: FIXTURES.referee,
+        legacyReferrer: FIXTURES.referrerV0,
+        referrer,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const { owner, referee, pool, parent, version } =
+      await program.account.referrer.fetch(referrer);
+    const legacy = await provider.connection.getAccountInfo(
+      FIXTURES.referrerV0
+    );
+    expect(owner).deep.equal(FIXTURES.referrerOwner);
+    expect(referee).deep.equal(FIXTURES.referee);
+    expect(pool).deep.equal(web3.PublicKey.default);
+    expect(parent).deep.equal(web3.PublicKey.default);
+    expect(version).equal(1);
+    expect(legacy).to.be.null;
+    // The referee paid for the legacy binding and gets its rent back
+    expect(await getLamports(FIXTURES.referee, provider)).equal(
+      refereeBalance + legacyRent
+    );
+  });
+
+  it("migrate a paused pool in the baseline layout to pause flags", async () => {
+    await program.methods
+      .migratePool(0, 0)
+      .accounts({
+        authority: provider.publicKey,
+        programState,
+        pool: FIXTURES.pausedPoolV0,
+        mintA: FIXTURES.mintX,
+        mintB: FIXTURES.mintY,
+        poolIndexA: getPoolIndexAddress(
+          FIXTURES.mintX,
+          0,
+          program.programId
+        )[0],
+        poolIndexB: getPoolIndexAddress(
+          FIXTURES.mintY,
+          0,
+          program.programId
+        )[0],
+        prevPoolIndexA: null,
+        prevPoolIndexB: null,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+
+    const { state, pauseFlags } = await program.account.pool.fetch(
+      FIXTURES.pausedPoolV0
+    );
+    expect(state).equal(STATE.Initialized);
+    expect(pauseFlags).equal(
+      PAUSE.Swap | PAUSE.Deposit | PAUSE.Withdraw | PAUSE.FeeClaim
+    );
+  });
+
+  it("initialized a pool of (B,C)", async () => {
+    const { pool, mintA, mintB, treasuryA, treasuryB, lpMint, escrow } =
+      getPoolAccounts(texture.B.mint.publicKey, texture.C.mint.publicKey);
+
+    // Anyone can fund the LP mint address ahead of the pool
+    await transferLamports(1000, lpMint, provider);
+    await program.methods
+      .initialize(
+        texture.B.amount.deposit,
+        texture.C.amount.deposit,
+        ZERO,
+        ZERO,
+        0,
+        0
+      )
+      .accounts({
+        authority: provider.publicKey,
+        programState,
+        platformConfig,
+        pool,
+        mintA,
+        srcA: utils.token.associatedAddress({
+          owner: provider.publicKey,
+          mint: mintA,
+        }),
+        treasuryA,
+        mintB,
+        srcB: utils.token.associatedAddress({
+          owner: provider.publicKey,
+          mint: mintB,
+        }),
+        treasuryB,
+        lpMint,
+        metadataA: null,
+        metadataB: null,
+        dstLp: getLpTokenAccount(provider.publicKey, lpMint),
+        feeSrc: null,
+        feeDst: null,
+        escrow,
+        tokenRegistryA: getTokenRegistryAddress(mintA, program.programId)[0],
+        tokenRegistryB: getTokenRegistryAddress(mintB, program.programId)[0],
+        poolIndexA: getPoolIndexAddress(mintA, 0, program.programId)[0],
+        poolIndexB: getPoolIndexAddress(mintB, 0, program.programId)[0],
+        prevPoolIndexA: null,
+        prevPoolIndexB: null,
+        taxman: provider.publicKey,
+        tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+        token2022Program: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const { state } = await program.account.pool.fetch(pool);
+    expect(state).deep.equal(STATE.Initialized);
+  });
+
+  it("reject canceling the pool of (B,C) by a non-authority", async () => {
+    const { pool } = getPoolAccounts(
+      texture.B.mint.publicKey,
+      texture.C.mint.publicKey
+    );
+
+    try {
+      await program.methods
+        .cancelPool()
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          programState,
+          pool,
+          accessControl: null,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the pool is canceled by a non-authority");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("Unauthorized");
+    }
+  });
+
+  it("reject closing the pool of (B,C) before it is canceled", async () => {
+    const accounts = getPoolAccounts(
+      texture.B.mint.publicKey,
+      texture.C.mint.publicKey
+    );
+
+    try {
+      await program.methods
+        .closePool(0, 0)
+        .accounts({
+          authority: provider.publicKey,
+          ...accounts,
+          dstA: utils.token.associatedAddress({
+            owner: provider.publicKey,
+            mint: accounts.mintA,
+          }),
+          dstB: utils.token.associatedAddress({
+            owner: provider.publicKey,
+            mint: accounts.mintB,
+          }),
+          tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+          lpTokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+      expect.fail("an active pool is closed");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidState");
+    }
+  });
+
+  it("pause withdrawals in the pool of (B,C)", async () => {
+    const { pool } = getPoolAccounts(
+      texture.B.mint.publicKey,
+      texture.C.mint.publicKey
+    );
+
+    await program.methods
+      .pause(PAUSE.Withdraw)
+      .accounts({
+        authority: provider.publicKey,
+        pool,
+        accessControl: null,
+        platformConfig,
+      })
+      .rpc();
+
+    const { pauseFlags } = await program.account.pool.fetch(pool);
+    expect(pauseFlags).equal(PAUSE.Withdraw);
+  });
+
+  it("a pool manager cancels the pool of (B,C)", async () => {
+    const { pool } = getPoolAccounts(
+      texture.B.mint.publicKey,
+      texture.C.mint.publicKey
+    );
+    const [accessControl] = getAccessControlAddress(pool, program.programId);
+
+    await program.methods
+      .grantRole(texture.Bob.keypair.publicKey, ROLE.PoolManager)
+      .accounts({
+        authority: provider.publicKey,
+        pool,
+        accessControl,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    await program.methods
+      .cancelPool()
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        programState,
+        pool,
+        accessControl,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+
+    // A canceled pool cannot be resumed, so it lifts the withdrawal pause
+    const { state, pauseFlags } = await program.account.pool.fetch(pool);
+    expect(state).deep.equal(STATE.Canceled);
+    expect(pauseFlags & PAUSE.Withdraw).equal(0);
+  });
+
+  it("reject closing the pool of (B,C) with outstanding liquidity", async () => {
+    const accounts = getPoolAccounts(
+      texture.B.mint.publicKey,
+      texture.C.mint.publicKey
+    );
+    const dstA = utils.token.associatedAddress({
+      owner: provider.publicKey,
+      mint: accounts.mintA,
+    });
+    const dstB = utils.token.associatedAddress({
+      owner: provider.publicKey,
+      mint: accounts.mintB,
+    });
+
+    // Only the authority can close the pool
+    try {
+      await program.methods
+        .closePool(0, 0)
+        .accounts({
+          authority: texture.Alice.keypair.publicKey,
+          ...accounts,
+          dstA: texture.Alice.tokenAccount(accounts.mintA),
+          dstB: texture.Alice.tokenAccount(accounts.mintB),
+          tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+          lpTokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .signers([texture.Alice.keypair])
+        .rpc();
+      expect.fail("the pool is closed by a non-authority");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("Unauthorized");
+    }
+
+    // The LP tokens of the authority are still outstanding
+    try {
+      await program.methods
+        .closePool(0, 0)
+        .accounts({
+          authority: provider.publicKey,
+          ...accounts,
+          dstA,
+          dstB,
+          tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+          lpTokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+      expect.fail("a pool with liquidity is closed");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("InvalidState");
+    }
+  });
+
+  it("Bob binds to Alice in the pool of (B,C)", async () => {
+    const [referrer] = await getReferrerAddress(
+      texture.Bob.keypair.publicKey,
+      poolBC,
+      program.programId
+    );
+
+    await program.methods
+      .createReferrer(texture.Alice.keypair.publicKey)
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        pool: poolBC,
+        referrer,
+        parent: (
+          await getReferrerAddress(
+            texture.Alice.keypair.publicKey,
+            poolBC,
+            program.programId
+          )
+        )[0],
+        referralStats: getReferralStatsAddress(
+          texture.Alice.keypair.publicKey,
+          program.programId
+        )[0],
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+
+    const { pool } = await program.account.referrer.fetch(referrer);
+    expect(pool).deep.eq(poolBC);
+  });
+
+  it("withdraw all the liquidity then close the pool of (B,C)", async () => {
+    const accounts = getPoolAccounts(
+      texture.B.mint.publicKey,
+      texture.C.mint.publicKey
+    );
+    const dstA = utils.token.associatedAddress({
+      owner: provider.publicKey,
+      mint: accounts.mintA,
+    });
+    const dstB = utils.token.associatedAddress({
+      owner: provider.publicKey,
+      mint: accounts.mintB,
+    });
+    const srcLp = getLpTokenAccount(provider.publicKey, accounts.lpMint);
+    const lp = await getTokenBalance(srcLp, provider);
+
+    // A canceled pool is withdraw-only
+    await program.methods
+      .removeLiquidity(lp)
+      .accounts({
+        authority: provider.publicKey,
+        pool: accounts.pool,
+        platformConfig,
+        mintA: accounts.mintA,
+        treasuryA: accounts.treasuryA,
+        dstA,
+        mintB: accounts.mintB,
+        treasuryB: accounts.treasuryB,
+        dstB,
+        lpMint: accounts.lpMint,
+        srcLp,
+        escrow: accounts.escrow,
+        tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+        lpTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    // A pending change of the pool must be canceled first
+    const eta =
+      (await getCurrentTimestamp(provider.connection)) + TIMELOCK_DELAY;
+    await program.methods
+      .updateReferralFee(new BN(0), new BN(eta))
+      .accounts({
+        authority: provider.publicKey,
+        pool: accounts.pool,
+        accessControl: null,
+        platformConfig,
+        pendingChange: accounts.pendingReferralFeeChange,
+        systemProgram: web3.SystemProgram.programId,
+      })
+      .rpc();
+    try {
+      await program.methods
+        .closePool(0, 0)
+        .accounts({
+          authority: provider.publicKey,
+          ...accounts,
+          dstA,
+          dstB,
+          tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+          tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+          lpTokenProgram: TOKEN_2022_PROGRAM_ID,
+          associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+          systemProgram: web3.SystemProgram.programId,
+          rent: web3.SYSVAR_RENT_PUBKEY,
+        })
+        .rpc();
+      expect.fail("a pool with a pending change is closed");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("PendingChangeOpen");
+    }
+    await program.methods
+      .cancelChange()
+      .accounts({
+        authority: provider.publicKey,
+        programState,
+        proposer: provider.publicKey,
+        pendingChange: accounts.pendingReferralFeeChange,
+        pool: accounts.pool,
+      })
+      .rpc();
+
+    await program.methods
+      .closePool(0, 0)
+      .accounts({
+        authority: provider.publicKey,
+        ...accounts,
+        dstA,
+        dstB,
+        tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+        lpTokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const pool = await provider.connection.getAccountInfo(accounts.pool);
+    const treasuryA = await provider.connection.getAccountInfo(
+      accounts.treasuryA
+    );
+    const { pools } = await program.account.poolIndex.fetch(
+      accounts.poolIndexA
+    );
+    const lpMint = await provider.connection.getAccountInfo(accounts.lpMint);
+    const accessControl = await provider.connection.getAccountInfo(
+      accounts.accessControl
+    );
+    expect(pool).to.be.null;
+    expect(treasuryA).to.be.null;
+    expect(lpMint).to.be.null;
+    expect(accessControl).to.be.null;
+    expect(pools.some((key) => key.equals(accounts.pool))).to.be.false;
+  });
+
+  it("Bob closes his binding in the closed pool of (B,C)", async () => {
+    const [referrer] = await getReferrerAddress(
+      texture.Bob.keypair.publicKey,
+      poolBC,
+      program.programId
+    );
+
+    const [referralStats] = getReferralStatsAddress(
+      texture.Alice.keypair.publicKey,
+      program.programId
+    );
+    const { totalReferees: prevTotalReferees } =
+      await program.account.referralStats.fetch(referralStats);
+
+    // A closed pool follows the cooldown of the first config
+    await program.methods
+      .closeReferrer()
+      .accounts({
+        authority: texture.Bob.keypair.publicKey,
+        pool: poolBC,
+        platformConfig,
+        referrer,
+        referralStats,
+      })
+      .signers([texture.Bob.keypair])
+      .rpc();
+
+    const info = await provider.connection.getAccountInfo(referrer);
+    const { totalReferees } = await program.account.referralStats.fetch(
+      referralStats
+    );
+    expect(info).to.be.null;
+    expect(totalReferees.toNumber()).equal(prevTotalReferees.toNumber() - 1);
+  });
+
+  it("re-create the closed pool of (B,C) without its former roles", async () => {
+    const {
+      pool,
+      mintA,
+      mintB,
+      treasuryA,
+      treasuryB,
+      lpMint,
+      escrow,
+      accessControl,
+      poolIndexA,
+    } = getPoolAccounts(texture.B.mint.publicKey, texture.C.mint.publicKey);
+    const dstLp = getLpTokenAccount(provider.publicKey, lpMint);
+
+    // The LP account of the closed pool is reused
+    await program.methods
+      .initialize(
+        texture.B.amount.deposit,
+        texture.C.amount.deposit,
+        ZERO,
+        ZERO,
+        0,
+        0
+      )
+      .accounts({
+        authority: provider.publicKey,
+        programState,
+        platformConfig,
+        pool,
+        mintA,
+        srcA: utils.token.associatedAddress({
+          owner: provider.publicKey,
+          mint: mintA,
+        }),
+        treasuryA,
+        mintB,
+        srcB: utils.token.associatedAddress({
+          owner: provider.publicKey,
+          mint: mintB,
+        }),
+        treasuryB,
+        lpMint,
+        metadataA: null,
+        metadataB: null,
+        dstLp,
+        feeSrc: null,
+        feeDst: null,
+        escrow,
+        tokenRegistryA: getTokenRegistryAddress(mintA, program.programId)[0],
+        tokenRegistryB: getTokenRegistryAddress(mintB, program.programId)[0],
+        poolIndexA,
+        poolIndexB: getPoolIndexAddress(mintB, 0, program.programId)[0],
+        prevPoolIndexA: null,
+        prevPoolIndexB: null,
+        taxman: provider.publicKey,
+        tokenProgram: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramA: utils.token.TOKEN_PROGRAM_ID,
+        tokenProgramB: utils.token.TOKEN_PROGRAM_ID,
+        token2022Program: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: utils.token.ASSOCIATED_PROGRAM_ID,
+        systemProgram: web3.SystemProgram.programId,
+        rent: web3.SYSVAR_RENT_PUBKEY,
+      })
+      .rpc();
+
+    const { state } = await program.account.pool.fetch(pool);
+    const lp = await getTokenBalance(dstLp, provider);
+    const { pools } = await program.account.poolIndex.fetch(poolIndexA);
+    expect(state).deep.equal(STATE.Initialized);
+    expect(lp.gt(ZERO)).to.be.true;
+    expect(pools.some((key) => key.equals(pool))).to.be.true;
+
+    // Bob managed the closed pool, not this one
+    expect(await provider.connection.getAccountInfo(accessControl)).to.be.null;
+    try {
+      await program.methods
+        .cancelPool()
+        .accounts({
+          authority: texture.Bob.keypair.publicKey,
+          programState,
+          pool,
+          accessControl: null,
+        })
+        .signers([texture.Bob.keypair])
+        .rpc();
+      expect.fail("a manager of the closed pool cancels the new pool");
+    } catch (er: any) {
+      expect(er.error.errorCode.code).equal("Unauthorized");
+    }
   });
 
   // it("update fee in the pool of (A,B)", async () => {
@@ -428,12 +3285,12 @@ describe("shield-dex-pg", () => {
   //     .updateFee(texture.fee)
   //     .accounts({
   //       authority: provider.publicKey,
-  //       pool: texture.poolAB.publicKey,
+  //       pool: poolAB,
   //     })
   //     .rpc({ skipPreflight: true });
 
   //   const { lpFee } = await program.account.pool.fetch(
-  //     texture.poolAB.publicKey
+  //     poolAB
   //   );
 
   //   expect(lpFee.eq(texture.fee)).to.be.true;
@@ -443,12 +3300,13 @@ describe("shield-dex-pg", () => {
   // //   await program.methods
   // //     .updateTax(texture.tax)
   // //     .accounts({
+  programState,
   // //       authority: provider.publicKey,
-  // //       pool: texture.poolAB.publicKey,
+  // //       pool: poolAB,
   // //     })
   // //     .rpc({ skipPreflight: true });
 
-  // //   const { tax } = await program.account.pool.fetch(texture.poolAB.publicKey);
+  // //   const { tax } = await program.account.pool.fetch(poolAB);
 
   // //   expect(tax.eq(texture.tax)).to.be.true;
   // // });
@@ -458,13 +3316,13 @@ describe("shield-dex-pg", () => {
   //     .pause()
   //     .accounts({
   //       authority: texture.Alice.keypair.publicKey,
-  //       pool: texture.poolAB.publicKey,
+  //       pool: poolAB,
   //     })
   //     .signers([texture.Alice.keypair])
   //     .rpc({ skipPreflight: true });
 
   //   const { state } = await program.account.pool.fetch(
-  //     texture.poolAB.publicKey
+  //     poolAB
   //   );
 
   //   expect(state).deep.equal(STATE.Paused);
@@ -475,13 +3333,13 @@ describe("shield-dex-pg", () => {
   //     .resume()
   //     .accounts({
   //       authority: texture.Alice.keypair.publicKey,
-  //       pool: texture.poolAB.publicKey,
+  //       pool: poolAB,
   //     })
   //     .signers([texture.Alice.keypair])
   //     .rpc({ skipPreflight: true });
 
   //   const { state } = await program.account.pool.fetch(
-  //     texture.poolAB.publicKey
+  //     poolAB
   //   );
 
   //   expect(state).deep.equal(STATE.Initialized);
@@ -499,7 +3357,7 @@ describe("shield-dex-pg", () => {
   //     )
   //     .accounts({
   //       authority: provider.publicKey,
-  //       pool: texture.poolBC.publicKey,
+  //       pool: poolBC,
   //       mintA: texture.B.mint.publicKey,
   //       srcA: utils.token.associatedAddress({
   //         owner: provider.publicKey,
@@ -530,11 +3388,10 @@ describe("shield-dex-pg", () => {
   //       systemProgram: web3.SystemProgram.programId,
   //       rent: web3.SYSVAR_RENT_PUBKEY,
   //     })
-  //     .signers([texture.poolBC])
   //     .rpc({ skipPreflight: true });
 
   //   const { authority, lpMint, mintA, mintB, lpFee, tax, state } =
-  //     await program.account.pool.fetch(texture.poolBC.publicKey);
+  //     await program.account.pool.fetch(poolBC);
   //   const { amount: a } = await texture.spl.account.account.fetch(
   //     utils.token.associatedAddress({
   //       owner: escrowBC,
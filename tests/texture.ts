@@ -56,12 +56,9 @@ export default class Texture {
   public fee = new BN(2500000);
   public tax = new BN(1500000);
 
-  public platformConfig = new web3.Keypair();
   // Referrer
   public referrer = new web3.Keypair();
 
-  public poolAB = new web3.Keypair();
-  public poolBC = new web3.Keypair();
   public taxman = new web3.Keypair();
 
   // Tokens
@@ -75,6 +72,15 @@ export default class Texture {
 
   constructor(public readonly provider: AnchorProvider) {
     this.spl = splTokenProgram({ provider });
+    // Pools take their mints sorted, so A always comes before B
+    if (
+      Buffer.compare(
+        this.A.mint.publicKey.toBuffer(),
+        this.B.mint.publicKey.toBuffer()
+      ) > 0
+    ) {
+      [this.A.mint, this.B.mint] = [this.B.mint, this.A.mint];
+    }
     this.Alice = new Trader(this.provider.connection);
     this.Bob = new Trader(this.provider.connection);
   }
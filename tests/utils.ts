@@ -1,7 +1,56 @@
-import { web3, BN, utils, Provider } from "@coral-xyz/anchor";
+import { web3, BN, utils, Provider, Program, Idl } from "@coral-xyz/anchor";
 import { splTokenProgram } from "@coral-xyz/spl-token";
 
 export const REFERRER_SEED = Buffer.from("referrer");
+export const PROGRAM_STATE_SEED = Buffer.from("program_state");
+export const PLATFORM_CONFIG_SEED = Buffer.from("platform_config");
+export const PENDING_CHANGE_SEED = Buffer.from("pending_change");
+export const TOKEN_REGISTRY_SEED = Buffer.from("token_registry");
+export const POOL_SEED = Buffer.from("pool");
+export const POOL_INDEX_SEED = Buffer.from("pool_index");
+export const ACCESS_CONTROL_SEED = Buffer.from("access_control");
+export const REFERRAL_REWARDS_SEED = Buffer.from("referral_rewards");
+export const REFERRAL_VAULT_SEED = Buffer.from("referral_vault");
+export const REFERRAL_CODE_SEED = Buffer.from("referral_code");
+export const REFERRAL_STATS_SEED = Buffer.from("referral_stats");
+export const SWAP_COUNTER_SEED = Buffer.from("swap_counter");
+export const SWAP_COUNTER_SHARDS = 16;
+export const EVENT_IX_TAG = Buffer.from("e445a52e51cb9a1d", "hex");
+
+export const TOKEN_2022_PROGRAM_ID = new web3.PublicKey(
+  "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb"
+);
+
+/**
+ * Accounts in the baseline layouts, loaded by the validator from tests/fixtures.
+ */
+export const FIXTURES = {
+  authority: new web3.PublicKey("FH5TP3x2dFNqb73AGbwDABn4PsKt9yEEa6K4MfuQnjkv"),
+  mintX: new web3.PublicKey("H5XTbr8HJFGcCmZDCnwSMaEu4eKiM3RttYCohBrCjHGj"),
+  mintY: new web3.PublicKey("5Kce14U25PJsgoC1SQQhhP7GBMWhuw7bxLuRQticP7NA"),
+  poolV0: new web3.PublicKey("4wuwFe1smEpB4KvF4F6nvgac1qiy6eQ7K5ZD1um37UfW"),
+  pausedPoolV0: new web3.PublicKey(
+    "BAKVh2dqKXTVknNWRuazQQ8EvBhgcFtGbw8PEpMptEK"
+  ),
+  platformConfigV0: new web3.PublicKey(
+    "3qyGyBgRX1EpnneakThCMpfpgxvU9EbitQztz7hReeJL"
+  ),
+  referrerV0: new web3.PublicKey(
+    "5BhJH2gmJ3LtKQiQE4qB6uzdX7XqdYbgSBieDqjqsTH8"
+  ),
+  referee: new web3.PublicKey("FX1zZoQvZYHBDHb8Vg6WsgajcuZCaYh5YrnBsQtrcFS1"),
+  referrerOwner: new web3.PublicKey(
+    "FRMuVPvx8fqcAUTscihVX9XotgbEKgArSWiqP24Y4MfA"
+  ),
+};
+
+export const PARAM_CHANGE = {
+  LpFee: 0,
+  ReferralFee: 1,
+  FeeTier: 2,
+  Tax: 3,
+  TimelockDelay: 4,
+};
 
 export const asyncWait = (s: number) =>
   new Promise((resolve) => setTimeout(resolve, s * 1000));
@@ -16,6 +65,15 @@ export const getCurrentTimestamp = async (
   return bn.toNumber();
 };
 
+export const waitUntil = async (
+  connection: web3.Connection,
+  timestamp: number
+) => {
+  while ((await getCurrentTimestamp(connection)) <= timestamp) {
+    await asyncWait(1);
+  }
+};
+
 export const initializeMint = async (
   decimals: number,
   token: web3.Keypair,
@@ -35,18 +93,176 @@ export const initializeMint = async (
     .rpc({ maxRetries: 5 });
 };
 
+/**
+ * The referrer binding of a referee in a pool, or the global one without pool.
+ */
 export async function getReferrerAddress(
   authority: web3.PublicKey,
+  pool: web3.PublicKey | null,
   programId: web3.PublicKey
 ): Promise<[web3.PublicKey, number]> {
+  const scope = pool || web3.PublicKey.default;
   const [address, bump] = web3.PublicKey.findProgramAddressSync(
-    [REFERRER_SEED, authority.toBuffer()],
+    [REFERRER_SEED, scope.toBuffer(), authority.toBuffer()],
     programId
   );
 
   return [address, bump];
 }
 
+/**
+ * The balance of a referrer in a mint.
+ */
+export function getReferralRewardsAddress(
+  owner: web3.PublicKey,
+  mint: web3.PublicKey,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [REFERRAL_REWARDS_SEED, owner.toBuffer(), mint.toBuffer()],
+    programId
+  );
+}
+
+export function getReferralCodeAddress(
+  code: string,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [REFERRAL_CODE_SEED, Buffer.from(code)],
+    programId
+  );
+}
+
+export function getReferralStatsAddress(
+  owner: web3.PublicKey,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [REFERRAL_STATS_SEED, owner.toBuffer()],
+    programId
+  );
+}
+
+export function getReferralVaultAddress(
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [REFERRAL_VAULT_SEED],
+    programId
+  );
+}
+
+export function getProgramStateAddress(
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [PROGRAM_STATE_SEED],
+    programId
+  );
+}
+
+/**
+ * The shard of the swap counter a pool counts its swaps in, picked by the pool id.
+ */
+export function getSwapCounterAddress(
+  poolId: BN,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [SWAP_COUNTER_SEED, Buffer.from([poolId.modn(SWAP_COUNTER_SHARDS)])],
+    programId
+  );
+}
+
+export function getPlatformConfigAddress(
+  index: number,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  const seed = Buffer.alloc(2);
+  seed.writeUInt16LE(index);
+  return web3.PublicKey.findProgramAddressSync(
+    [PLATFORM_CONFIG_SEED, seed],
+    programId
+  );
+}
+
+export function getPendingChangeAddress(
+  target: web3.PublicKey,
+  kind: number,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [PENDING_CHANGE_SEED, target.toBuffer(), Buffer.from([kind])],
+    programId
+  );
+}
+
+export function getTokenRegistryAddress(
+  mint: web3.PublicKey,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [TOKEN_REGISTRY_SEED, mint.toBuffer()],
+    programId
+  );
+}
+
+export function sortMints(
+  mintX: web3.PublicKey,
+  mintY: web3.PublicKey
+): [web3.PublicKey, web3.PublicKey] {
+  return Buffer.compare(mintX.toBuffer(), mintY.toBuffer()) < 0
+    ? [mintX, mintY]
+    : [mintY, mintX];
+}
+
+/**
+ * The canonical pool of a pair of mints in a platform config.
+ * The mints can be given in any order.
+ */
+export function getPoolAddress(
+  platformConfig: web3.PublicKey,
+  mintX: web3.PublicKey,
+  mintY: web3.PublicKey,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  const [mintA, mintB] = sortMints(mintX, mintY);
+  return web3.PublicKey.findProgramAddressSync(
+    [POOL_SEED, platformConfig.toBuffer(), mintA.toBuffer(), mintB.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * The roles granted in a pool.
+ */
+export function getAccessControlAddress(
+  pool: web3.PublicKey,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  return web3.PublicKey.findProgramAddressSync(
+    [ACCESS_CONTROL_SEED, pool.toBuffer()],
+    programId
+  );
+}
+
+/**
+ * A page of the pools trading a mint.
+ */
+export function getPoolIndexAddress(
+  mint: web3.PublicKey,
+  page: number,
+  programId: web3.PublicKey
+): [web3.PublicKey, number] {
+  const pageBuffer = Buffer.alloc(4);
+  pageBuffer.writeUInt32LE(page);
+  return web3.PublicKey.findProgramAddressSync(
+    [POOL_INDEX_SEED, mint.toBuffer(), pageBuffer],
+    programId
+  );
+}
+
 export const initializeAccount = async (
   mint: web3.PublicKey,
   splProgram: ReturnType<typeof splTokenProgram>
@@ -103,6 +319,260 @@ export const initializeAccount = async (
   return await provider.sendAndConfirm(tx, undefined, { maxRetries: 5 });
 };
 
+/**
+ * A Token-2022 mint carrying a close authority, one of the extensions the pools
+ * treat as dangerous, with the associated account of the provider.
+ */
+const createAssociatedTokenAccountInstruction = (
+  owner: web3.PublicKey,
+  mint: web3.PublicKey,
+  tokenProgram: web3.PublicKey
+) =>
+  new web3.TransactionInstruction({
+    keys: [
+      { pubkey: owner, isSigner: true, isWritable: true },
+      {
+        pubkey: getAssociatedTokenAddress(owner, mint, tokenProgram),
+        isSigner: false,
+        isWritable: true,
+      },
+      { pubkey: owner, isSigner: false, isWritable: false },
+      { pubkey: mint, isSigner: false, isWritable: false },
+      {
+        pubkey: web3.SystemProgram.programId,
+        isSigner: false,
+        isWritable: false,
+      },
+      { pubkey: tokenProgram, isSigner: false, isWritable: false },
+    ],
+    programId: utils.token.ASSOCIATED_PROGRAM_ID,
+    data: Buffer.from([]),
+  });
+
+const borshString = (value: string) => {
+  const length = Buffer.alloc(4);
+  length.writeUInt32LE(Buffer.byteLength(value));
+  return Buffer.concat([length, Buffer.from(value)]);
+};
+
+export const initializeToken2022Mint = async (
+  decimals: number,
+  token: web3.Keypair,
+  provider: Provider
+) => {
+  if (!provider.publicKey || !provider.sendAndConfirm)
+    throw new Error("Invalid wallet");
+  // Base account, account type and the close authority extension
+  const space = 165 + 1 + 4 + 32;
+  const lamports =
+    await provider.connection.getMinimumBalanceForRentExemption(space);
+  const initializeMintCloseAuthority = new web3.TransactionInstruction({
+    keys: [{ pubkey: token.publicKey, isSigner: false, isWritable: true }],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([Buffer.from([25, 1]), provider.publicKey.toBuffer()]),
+  });
+  // Without a freeze authority
+  const initializeMint2 = new web3.TransactionInstruction({
+    keys: [{ pubkey: token.publicKey, isSigner: false, isWritable: true }],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([
+      Buffer.from([20, decimals]),
+      provider.publicKey.toBuffer(),
+      Buffer.from([0]),
+    ]),
+  });
+  const createAccount = createAssociatedTokenAccountInstruction(
+    provider.publicKey,
+    token.publicKey,
+    TOKEN_2022_PROGRAM_ID
+  );
+  const tx = new web3.Transaction().add(
+    web3.SystemProgram.createAccount({
+      fromPubkey: provider.publicKey,
+      newAccountPubkey: token.publicKey,
+      space,
+      lamports,
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    initializeMintCloseAuthority,
+    initializeMint2,
+    createAccount
+  );
+  return await provider.sendAndConfirm(tx, [token], { maxRetries: 5 });
+};
+
+export const initializeToken2022MintWithTransferFee = async (
+  decimals: number,
+  token: web3.Keypair,
+  feeBasisPoints: number,
+  amount: BN,
+  provider: Provider
+) => {
+  if (!provider.publicKey || !provider.sendAndConfirm)
+    throw new Error("Invalid wallet");
+  // Base account, account type and the transfer fee config extension
+  const space = 165 + 1 + 4 + 108;
+  const lamports =
+    await provider.connection.getMinimumBalanceForRentExemption(space);
+  // Uncapped fee, without a config nor a withdraw authority
+  const fee = Buffer.alloc(2);
+  fee.writeUInt16LE(feeBasisPoints);
+  const initializeTransferFeeConfig = new web3.TransactionInstruction({
+    keys: [{ pubkey: token.publicKey, isSigner: false, isWritable: true }],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([
+      Buffer.from([26, 0, 0, 0]),
+      fee,
+      new BN("18446744073709551615").toArrayLike(Buffer, "le", 8),
+    ]),
+  });
+  // Without a freeze authority
+  const initializeMint2 = new web3.TransactionInstruction({
+    keys: [{ pubkey: token.publicKey, isSigner: false, isWritable: true }],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([
+      Buffer.from([20, decimals]),
+      provider.publicKey.toBuffer(),
+      Buffer.from([0]),
+    ]),
+  });
+  const mintTo = new web3.TransactionInstruction({
+    keys: [
+      { pubkey: token.publicKey, isSigner: false, isWritable: true },
+      {
+        pubkey: getAssociatedTokenAddress(
+          provider.publicKey,
+          token.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        isSigner: false,
+        isWritable: true,
+      },
+      { pubkey: provider.publicKey, isSigner: true, isWritable: false },
+    ],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([
+      Buffer.from([7]),
+      amount.toArrayLike(Buffer, "le", 8),
+    ]),
+  });
+  const tx = new web3.Transaction().add(
+    web3.SystemProgram.createAccount({
+      fromPubkey: provider.publicKey,
+      newAccountPubkey: token.publicKey,
+      space,
+      lamports,
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    initializeTransferFeeConfig,
+    initializeMint2,
+    createAssociatedTokenAccountInstruction(
+      provider.publicKey,
+      token.publicKey,
+      TOKEN_2022_PROGRAM_ID
+    ),
+    mintTo
+  );
+  return await provider.sendAndConfirm(tx, [token], { maxRetries: 5 });
+};
+
+export const initializeToken2022MintWithMetadata = async (
+  decimals: number,
+  token: web3.Keypair,
+  name: string,
+  symbol: string,
+  amount: BN,
+  provider: Provider
+) => {
+  if (!provider.publicKey || !provider.sendAndConfirm)
+    throw new Error("Invalid wallet");
+  const uri = "";
+  // Base account, account type and the metadata pointer extension
+  const space = 165 + 1 + 4 + 64;
+  // The metadata grows the mint once initialized, its rent is paid upfront
+  const metadataSpace =
+    4 + 64 + 4 + name.length + 4 + symbol.length + 4 + uri.length + 4;
+  const lamports = await provider.connection.getMinimumBalanceForRentExemption(
+    space + metadataSpace
+  );
+  // The mint holds its own metadata
+  const initializeMetadataPointer = new web3.TransactionInstruction({
+    keys: [{ pubkey: token.publicKey, isSigner: false, isWritable: true }],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([
+      Buffer.from([39, 0]),
+      provider.publicKey.toBuffer(),
+      token.publicKey.toBuffer(),
+    ]),
+  });
+  const initializeMint2 = new web3.TransactionInstruction({
+    keys: [{ pubkey: token.publicKey, isSigner: false, isWritable: true }],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([
+      Buffer.from([20, decimals]),
+      provider.publicKey.toBuffer(),
+      Buffer.from([0]),
+    ]),
+  });
+  const initializeMetadata = new web3.TransactionInstruction({
+    keys: [
+      { pubkey: token.publicKey, isSigner: false, isWritable: true },
+      { pubkey: provider.publicKey, isSigner: false, isWritable: false },
+      { pubkey: token.publicKey, isSigner: false, isWritable: false },
+      { pubkey: provider.publicKey, isSigner: true, isWritable: false },
+    ],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([
+      Buffer.from(
+        utils.sha256.hash("spl_token_metadata_interface:initialize_account"),
+        "hex"
+      ).subarray(0, 8),
+      borshString(name),
+      borshString(symbol),
+      borshString(uri),
+    ]),
+  });
+  const mintTo = new web3.TransactionInstruction({
+    keys: [
+      { pubkey: token.publicKey, isSigner: false, isWritable: true },
+      {
+        pubkey: getAssociatedTokenAddress(
+          provider.publicKey,
+          token.publicKey,
+          TOKEN_2022_PROGRAM_ID
+        ),
+        isSigner: false,
+        isWritable: true,
+      },
+      { pubkey: provider.publicKey, isSigner: true, isWritable: false },
+    ],
+    programId: TOKEN_2022_PROGRAM_ID,
+    data: Buffer.concat([
+      Buffer.from([7]),
+      amount.toArrayLike(Buffer, "le", 8),
+    ]),
+  });
+  const tx = new web3.Transaction().add(
+    web3.SystemProgram.createAccount({
+      fromPubkey: provider.publicKey,
+      newAccountPubkey: token.publicKey,
+      space,
+      lamports,
+      programId: TOKEN_2022_PROGRAM_ID,
+    }),
+    initializeMetadataPointer,
+    initializeMint2,
+    initializeMetadata,
+    createAssociatedTokenAccountInstruction(
+      provider.publicKey,
+      token.publicKey,
+      TOKEN_2022_PROGRAM_ID
+    ),
+    mintTo
+  );
+  return await provider.sendAndConfirm(tx, [token], { maxRetries: 5 });
+};
+
 export const mintTo = async (
   amount: BN,
   mint: web3.PublicKey,
@@ -140,6 +610,99 @@ export const transferLamports = async (
   return await provider.sendAndConfirm(tx, undefined, { maxRetries: 5 });
 };
 
+/**
+ * The associated account of an owner in a mint of the given token program.
+ */
+export function getAssociatedTokenAddress(
+  owner: web3.PublicKey,
+  mint: web3.PublicKey,
+  tokenProgram: web3.PublicKey
+): web3.PublicKey {
+  const [address] = web3.PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), tokenProgram.toBuffer(), mint.toBuffer()],
+    utils.token.ASSOCIATED_PROGRAM_ID
+  );
+  return address;
+}
+
+/**
+ * LP mints live in Token-2022, so their associated accounts are derived with it.
+ */
+export function getLpTokenAccount(
+  owner: web3.PublicKey,
+  lpMint: web3.PublicKey
+): web3.PublicKey {
+  const [address] = web3.PublicKey.findProgramAddressSync(
+    [owner.toBuffer(), TOKEN_2022_PROGRAM_ID.toBuffer(), lpMint.toBuffer()],
+    utils.token.ASSOCIATED_PROGRAM_ID
+  );
+  return address;
+}
+
+export const getTokenBalance = async (
+  address: web3.PublicKey,
+  provider: Provider
+) => {
+  const {
+    value: { amount },
+  } = await provider.connection.getTokenAccountBalance(address);
+  return new BN(amount);
+};
+
+/**
+ * Compute units consumed by a transaction, waiting for it to be confirmed.
+ */
+export const getComputeUnits = async (
+  txId: string,
+  provider: Provider,
+  retries = 10
+): Promise<number> => {
+  for (let i = 0; i < retries; i++) {
+    const tx = await provider.connection.getTransaction(txId, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    if (tx?.meta?.computeUnitsConsumed !== undefined)
+      return tx.meta.computeUnitsConsumed;
+    await asyncWait(0.5);
+  }
+  throw new Error(`Cannot read the compute units of ${txId}`);
+};
+
+/**
+ * Events emitted by a transaction through self-invocations, waiting for it to be confirmed.
+ */
+export const getEvents = async (
+  txId: string,
+  program: Program<Idl>,
+  retries = 10
+) => {
+  for (let i = 0; i < retries; i++) {
+    const tx = await program.provider.connection.getTransaction(txId, {
+      commitment: "confirmed",
+      maxSupportedTransactionVersion: 0,
+    });
+    if (tx?.meta?.innerInstructions) {
+      const keys = tx.transaction.message.staticAccountKeys;
+      return tx.meta.innerInstructions
+        .flatMap(({ instructions }) => instructions)
+        .filter(({ programIdIndex }) =>
+          keys[programIdIndex].equals(program.programId)
+        )
+        .map(({ data }) => Buffer.from(utils.bytes.bs58.decode(data)))
+        .filter((data) => data.subarray(0, 8).equals(EVENT_IX_TAG))
+        .map((data) =>
+          program.coder.events.decode(
+            utils.bytes.base64.encode(data.subarray(8))
+          )
+        )
+        .filter((event) => event !== null);
+    }
+    await asyncWait(0.5);
+  }
+  throw new Error(`Cannot read the events of ${txId}`);
+};
+
 export const getLamports = async (
   address: web3.PublicKey,
   provider: Provider